@@ -0,0 +1,48 @@
+//! Builds the whitelist Merkle tree from a `wallet,allocation` CSV.
+//!
+//! Prints the root to pass to `InitWhiteList`, followed by one
+//! `wallet,allocation,proof` line per entry where the proof is a
+//! `:`-separated list of hex encoded nodes to pass to `SwapSOLToken`.
+
+use std::{env, fs, process};
+use whitelist_program::merkle::MerkleTree;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn main() {
+    let csv_path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: whitelist-allowlist <allowlist.csv>");
+            process::exit(1);
+        }
+    };
+
+    let csv = fs::read_to_string(&csv_path).unwrap_or_else(|err| {
+        eprintln!("Could not read {}: {}", csv_path, err);
+        process::exit(1);
+    });
+
+    let entries = MerkleTree::parse_csv(&csv).unwrap_or_else(|err| {
+        eprintln!("Could not parse {}: {}", csv_path, err);
+        process::exit(1);
+    });
+
+    let tree = MerkleTree::new(&entries);
+
+    println!("root,{}", to_hex(&tree.root()));
+
+    for (index, entry) in entries.iter().enumerate() {
+        let proof = tree
+            .proof(index)
+            .unwrap_or_default()
+            .iter()
+            .map(|node| to_hex(node))
+            .collect::<Vec<String>>()
+            .join(":");
+
+        println!("{},{},{}", entry.user, entry.allocation, proof);
+    }
+}
@@ -1,7 +1,9 @@
 use solana_program::{
-    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult,
+    program_error::PrintProgramError, pubkey::Pubkey,
 };
 
+use crate::error::WhiteListError;
 use crate::processor::WhiteListProcessor;
 
 entrypoint!(process_instruction);
@@ -11,5 +13,10 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    WhiteListProcessor::process(program_id, accounts, instruction_data)
+    if let Err(error) = WhiteListProcessor::process(program_id, accounts, instruction_data) {
+        // Printing the error, so custom codes show up with their message in the logs
+        error.print::<WhiteListError>();
+        return Err(error);
+    }
+    Ok(())
 }
@@ -1,8 +1,14 @@
 use num_derive::FromPrimitive;
-use solana_program::program_error::ProgramError;
+use num_traits::FromPrimitive as FromPrimitiveTrait;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
 use thiserror::Error;
 
-#[derive(Clone, Debug, Error, FromPrimitive)]
+/// Errors that may be returned by the whitelist program, as `ProgramError::Custom` codes
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum WhiteListError {
     // Invalid Instruction
     #[error("Invalid Instruction data")]
@@ -29,8 +35,188 @@ pub enum WhiteListError {
     AccountNotWhitelisted,
 
     // AccountAlreadyRedeemed
-    #[error("Passed account has already redeemed SPL Token")]
+    #[error("Passed account has already redeemed its full allocation")]
     AccountAlreadyRedeemed,
+
+    // InvalidAllowlistEntry
+    #[error("Allowlist entry could not be parsed")]
+    InvalidAllowlistEntry,
+
+    // AllocationExceeded
+    #[error("Swap amount exceeds the user's allocation")]
+    AllocationExceeded,
+
+    // IncorrectWhitelistAdmin
+    #[error("Signer is not allowed to administer the whitelist")]
+    IncorrectWhitelistAdmin,
+
+    // IncorrectEntryAccount
+    #[error("Passed whitelist entry account does not belong to the user")]
+    IncorrectEntryAccount,
+
+    // ContributionTooSmall
+    #[error("Swap amount is below the minimum contribution")]
+    ContributionTooSmall,
+
+    // SaleNotStarted
+    #[error("Sale has not started yet")]
+    SaleNotStarted,
+
+    // SaleEnded
+    #[error("Sale has already ended")]
+    SaleEnded,
+
+    // InvalidSaleSchedule
+    #[error("Sale window or phases are invalid")]
+    InvalidSaleSchedule,
+
+    // PriceMismatch
+    #[error("Swap output deviates from the fixed sale price")]
+    PriceMismatch,
+
+    // SalePaused
+    #[error("Sale is paused")]
+    SalePaused,
+
+    // SaleNotEnded
+    #[error("Sale has not ended yet")]
+    SaleNotEnded,
+
+    // SaleModeMismatch
+    #[error("Instruction is not supported by the sale mode")]
+    SaleModeMismatch,
+
+    // HardCapExceeded
+    #[error("Purchase would exceed the sale's hard cap")]
+    HardCapExceeded,
+
+    // SoftCapNotReached
+    #[error("Sale has not reached its soft cap")]
+    SoftCapNotReached,
+
+    // SoftCapReached
+    #[error("Sale has reached its soft cap, contributions are final")]
+    SoftCapReached,
+
+    // NothingToClaim
+    #[error("Nothing left to claim or refund")]
+    NothingToClaim,
+
+    // ClaimsOutstanding
+    #[error("Users still have refunds or tokens to claim")]
+    ClaimsOutstanding,
+
+    // InvalidVestingSchedule
+    #[error("Vesting schedule is invalid")]
+    InvalidVestingSchedule,
+
+    // RegistryFull
+    #[error("Creator registry has no room for another sale")]
+    RegistryFull,
+
+    // IncorrectTokenProgram
+    #[error("Passed Token Program is not the SPL Token Program")]
+    IncorrectTokenProgram,
+
+    // IncorrectSystemProgram
+    #[error("Passed System Program is not the System Program")]
+    IncorrectSystemProgram,
+
+    // IncorrectSwapProgram
+    #[error("Passed Token Swap Program is not the expected Token Swap Program")]
+    IncorrectSwapProgram,
+
+    // IncorrectAssociatedTokenProgram
+    #[error("Passed Associated Token Program is not the SPL Associated Token Program")]
+    IncorrectAssociatedTokenProgram,
+
+    // AssociatedAccountMismatch
+    #[error("Passed token account is not the expected associated token account")]
+    AssociatedAccountMismatch,
+
+    // IncorrectPdaAccount
+    #[error("Passed account is not the expected program derived address")]
+    IncorrectPdaAccount,
+
+    // NotATokenAccount
+    #[error("Passed account is not a token account")]
+    NotATokenAccount,
+
+    // IncorrectUserTokenAccount
+    #[error("Passed token account does not belong to the user")]
+    IncorrectUserTokenAccount,
+
+    // MintMismatch
+    #[error("Passed token account or mint does not match the expected mint")]
+    MintMismatch,
+
+    // PoolMismatch
+    #[error("Passed accounts do not match the sale's token swap pool")]
+    PoolMismatch,
+
+    // VaultMismatch
+    #[error("Passed Y token vault is not the sale's vault")]
+    VaultMismatch,
+
+    // VaultDepleted
+    #[error("Y token vault has not enough tokens left")]
+    VaultDepleted,
+
+    // TreasuryMismatch
+    #[error("Passed treasury is not the sale's treasury")]
+    TreasuryMismatch,
+
+    // InsufficientSol
+    #[error("Not enough SOL for the operation")]
+    InsufficientSol,
+
+    // InvalidSaleConfig
+    #[error("Sale price or contribution limits are invalid")]
+    InvalidSaleConfig,
+
+    // InvalidSaleCaps
+    #[error("Sale caps are invalid")]
+    InvalidSaleCaps,
+
+    // SaleTermsLocked
+    #[error("Sale terms can't change once it has raised SOL")]
+    SaleTermsLocked,
+
+    // InvalidWithdrawAmount
+    #[error("Withdraw amount is zero or above the withdrawable amount")]
+    InvalidWithdrawAmount,
+
+    // MathOverflow
+    #[error("Arithmetic overflow")]
+    MathOverflow,
+
+    // InvalidStateVersion
+    #[error("State account layout version not supported, migrate it first")]
+    InvalidStateVersion,
+
+    // VaultNotEmpty
+    #[error("Y Token vault still holds tokens, withdraw them first")]
+    VaultNotEmpty,
+
+    // IncorrectMint
+    #[error("Token account does not hold the given mint")]
+    IncorrectMint,
+
+    // InsufficientFunds
+    #[error("Insufficient quote tokens")]
+    InsufficientFunds,
+
+    // InvalidEventData
+    #[error("Invalid event data")]
+    InvalidEventData,
+
+    // AmountNotPriceMultiple
+    #[error("Purchase amount is not a multiple of the fixed token price")]
+    AmountNotPriceMultiple,
+
+    // ProceedsOutstanding
+    #[error("Sale still holds proceeds, withdraw them first")]
+    ProceedsOutstanding,
 }
 
 impl From<WhiteListError> for ProgramError {
@@ -38,3 +224,18 @@ impl From<WhiteListError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl<T> DecodeError<T> for WhiteListError {
+    fn type_of() -> &'static str {
+        "WhiteList Error"
+    }
+}
+
+impl PrintProgramError for WhiteListError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitiveTrait,
+    {
+        msg!("Error: {}", self);
+    }
+}
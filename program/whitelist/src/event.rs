@@ -0,0 +1,167 @@
+//! Event records logged by the whitelist program
+
+use crate::error::WhiteListError::InvalidEventData;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    clock::UnixTimestamp, log::sol_log_data, program_error::ProgramError, pubkey::Pubkey,
+};
+
+/// Version byte prefixed to every event record
+pub const EVENT_VERSION: u8 = 1;
+
+/// Events emitted with `sol_log_data` once an instruction succeeded, packed as a version
+/// byte followed by their Borsh encoding. RPC nodes show them base64 encoded in the
+/// transaction logs as `Program data: <record>`.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum WhiteListEvent {
+    /// A pool or direct sale has been initialized
+    SaleInitialized {
+        sale: Pubkey,
+        creator: Pubkey,
+        y_mint: Pubkey,
+        sale_id: u64,
+        price_per_token_y: u64,
+        is_direct_sale: bool,
+        timestamp: UnixTimestamp,
+    },
+
+    /// A user bought `y_amount` Y tokens for `sol_amount` of the sale's quote mint, with a swap
+    /// or directly
+    Purchased {
+        sale: Pubkey,
+        user: Pubkey,
+        sol_amount: u64,
+        y_amount: u64,
+        price_per_token_y: u64,
+        timestamp: UnixTimestamp,
+    },
+
+    /// The price and pool binding of a sale have been updated
+    ConfigUpdated {
+        sale: Pubkey,
+        token_swap_pool_state: Pubkey,
+        price_per_token_y: u64,
+        is_fixed_price: bool,
+        timestamp: UnixTimestamp,
+    },
+
+    /// A user got `sol_amount` lamports back from a sale that missed its soft cap
+    Refunded {
+        sale: Pubkey,
+        user: Pubkey,
+        sol_amount: u64,
+        timestamp: UnixTimestamp,
+    },
+
+    /// A user claimed `y_amount` escrowed or vested Y tokens
+    Claimed {
+        sale: Pubkey,
+        user: Pubkey,
+        y_amount: u64,
+        timestamp: UnixTimestamp,
+    },
+}
+
+impl WhiteListEvent {
+    /// Unpacks a version prefixed, Borsh encoded event record, as logged by the program.
+    /// Short, trailing or otherwise malformed data is rejected with `InvalidEventData`.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let (version, rest) = data.split_first().ok_or(InvalidEventData)?;
+
+        if *version != EVENT_VERSION {
+            return Err(InvalidEventData.into());
+        }
+
+        Self::try_from_slice(rest).map_err(|_| InvalidEventData.into())
+    }
+
+    /// Packs the event into a version prefixed, Borsh encoded byte buffer
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![EVENT_VERSION];
+        // Serializing into a Vec can't fail
+        buf.extend_from_slice(&self.try_to_vec().unwrap());
+        buf
+    }
+
+    /// Logs the packed event as a single `sol_log_data` record
+    pub fn emit(&self) {
+        sol_log_data(&[&self.pack()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_all_events() {
+        let sale = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let events = vec![
+            WhiteListEvent::SaleInitialized {
+                sale,
+                creator: user,
+                y_mint: Pubkey::new_unique(),
+                sale_id: 1,
+                price_per_token_y: 2,
+                is_direct_sale: true,
+                timestamp: 3,
+            },
+            WhiteListEvent::Purchased {
+                sale,
+                user,
+                sol_amount: 4,
+                y_amount: 5,
+                price_per_token_y: 6,
+                timestamp: 7,
+            },
+            WhiteListEvent::ConfigUpdated {
+                sale,
+                token_swap_pool_state: Pubkey::new_unique(),
+                price_per_token_y: 8,
+                is_fixed_price: false,
+                timestamp: 9,
+            },
+            WhiteListEvent::Refunded {
+                sale,
+                user,
+                sol_amount: 10,
+                timestamp: 11,
+            },
+            WhiteListEvent::Claimed {
+                sale,
+                user,
+                y_amount: 12,
+                timestamp: 13,
+            },
+        ];
+
+        for check in events {
+            let packed = check.pack();
+            assert_eq!(packed[0], EVENT_VERSION);
+            assert_eq!(WhiteListEvent::unpack(&packed).unwrap(), check);
+        }
+    }
+
+    #[test]
+    fn unpack_malformed_events() {
+        let packed = WhiteListEvent::Claimed {
+            sale: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            y_amount: 1,
+            timestamp: 2,
+        }
+        .pack();
+
+        let invalid_event_data = Err(InvalidEventData.into());
+        assert_eq!(WhiteListEvent::unpack(&[]), invalid_event_data);
+        assert_eq!(
+            WhiteListEvent::unpack(&packed[..packed.len() - 1]),
+            invalid_event_data
+        );
+
+        let mut wrong_version = packed;
+        wrong_version[0] = EVENT_VERSION + 1;
+        assert_eq!(WhiteListEvent::unpack(&wrong_version), invalid_event_data);
+    }
+}
@@ -1,9 +1,40 @@
+//! Instruction types
+
+#![allow(clippy::too_many_arguments)]
+
 use crate::error::WhiteListError::InvalidInstruction;
-use solana_program::{program_error::ProgramError, pubkey::Pubkey};
-use std::convert::TryInto;
+use crate::state::SalePhase;
+use crate::{
+    find_whitelist_address, find_whitelist_entry_address, find_whitelist_registry_address,
+    find_whitelist_user_state_address, find_whitelist_vesting_vault_address, id,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    clock::UnixTimestamp,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::native_mint;
 
+/// Instructions supported by the whitelist program, packed as a version byte
+/// followed by their Borsh encoding (variant index, then fields in order)
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
 pub enum WhiteListInstruction {
-    /// Initializes the PDA with an array of allowed accounts
+    /// Initializes the PDA with the Merkle root of the allowed accounts
+    /// and the default per-user contribution limits (a maximum of 0 means uncapped).
+    /// Swaps are only allowed from `sale_start_ts` until `sale_end_ts` (0 means no end).
+    /// With `is_fixed_price`, every swap must return exactly `input_sol_amount / price_per_token_y`
+    /// Y tokens, `price_per_token_y` being in smallest units of the quote mint per smallest unit
+    /// of Y, and the pool has to be a constant price curve with Y as token B at that price.
+    /// The quote mint (wSOL, USDC, ...) is the mint of the pool's other side, and the
+    /// contribution limits are in its smallest units as well.
+    /// `sale_id` tells apart the creator's sales of the same Y token account, each of which
+    /// is listed in the creator's registry (seeds: `["whitelistregistry", creator]`).
+    /// The PDA must be the canonical derivation of its seeds, see `find_whitelist_address`.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -12,12 +43,18 @@ pub enum WhiteListInstruction {
     /// 2. [] Token Swap Pool State account
     /// 3. [] Y Token Mint account
     /// 4. [] Y Token account
-    /// 5. [] Native sol token account
+    /// 5. [] Pool Quote Token account, the other side of the pool
     /// 6. [] System program
+    /// 7. `[writable]` Creator Registry PDA account, created on the creator's first sale
     InitWhiteList {
-        whitelist_pda_bump: u8,
         price_per_token_y: u64,
-        authorized_addresses: Vec<Pubkey>,
+        merkle_root: [u8; 32],
+        min_contribution: u64,
+        max_contribution: u64,
+        sale_start_ts: UnixTimestamp,
+        sale_end_ts: UnixTimestamp,
+        is_fixed_price: bool,
+        sale_id: u64,
     },
 
     /// Creates an Associated token for the user and wraps a user's SOL token
@@ -31,6 +68,7 @@ pub enum WhiteListInstruction {
     /// 4. `[]` SPL Token program
     /// 5. `[]` SPL Associated Token program
     /// 6. [] Rent sysvar
+    /// 7. [] (Optional) Whitelist Global State Account, rejects wrapping while the sale is paused
     CreateAndWrapSOLToken { amount_to_be_wrapped: u64 },
 
     // Just Wraps SOL Token for the user
@@ -41,6 +79,7 @@ pub enum WhiteListInstruction {
     /// 1. `[writeable]` Associated Native SOL token account
     /// 2. [] System Program
     /// 3. [] SPL Token Program
+    /// 4. [] (Optional) Whitelist Global State Account, rejects wrapping while the sale is paused
     WrapSOLToken { amount_to_be_wrapped: u64 },
 
     /// Unwraps a native mint token account by transferring all its SOL to the destination account.
@@ -52,91 +91,1631 @@ pub enum WhiteListInstruction {
     /// 2. [] SPL Token Program
     UnwrapSOLToken,
 
-    ///   Swap SOL for predefined SPL Token
+    ///   Swap SOL, or whatever the sale's quote mint is, for predefined SPL Token
+    ///
+    ///   The user must prove that `(user, allocation)` is a leaf of the whitelist Merkle tree,
+    ///   or pass their whitelist entry account, in which case `allocation` and `proof` are ignored.
+    ///   The allocation overrides the global maximum contribution, 0 meaning no per-user cap.
+    ///   A user can swap several times until their cumulative contribution reaches the cap.
+    ///   In fixed price mode the pool must return exactly `input_sol_amount / price_per_token_y`
+    ///   Y tokens, the current sale phase's price taking precedence, and `input_sol_amount`
+    ///   must be a multiple of that price.
     ///
-    ///   0. `[signer]` User account who wants to swap
-    ///   1. `[writable]` Whitelist User State Account
-    ///   2. `[]` Whitelist Global State Account
+    ///   The user state PDA (seeds: `["whitelistuser", whitelist global state, user]`)
+    ///   is created and funded by the user on their first swap.
+    ///
+    ///   0. `[writable, signer]` User account who wants to swap
+    ///   1. `[writable]` Whitelist User State PDA Account
+    ///   2. `[writable]` Whitelist Global State Account
     ///   3. `[]` Token Swap State Account
     ///   4. `[]` Swap authority PDA Account
     ///   5. `[]` User Transfer Authority Token Account
-    ///   6. `[writable]` User Native SOL Token Account,
+    ///   6. `[writable]` User Quote Token Account,
     ///   7. `[writable]` User (**Token Y**) Token Account
-    ///   8. `[writable]` Token Swap Pool Native Sol Token Account.
+    ///   8. `[writable]` Token Swap Pool Quote Token Account.
     ///   9. `[writable]` Token Swap (**Token Y**) Token Account.
     ///  10. `[writable]` Pool Mint Token,
     ///  11. `[writable]` Pool Token Fee Account
     ///  12. `[writable]` Host fee account to receive additional trading fees
     ///  13. `[]` Token program id
     ///  14. `[]` Token Swap program id
+    ///  15. `[]` System program
+    ///  16. `[]` (Optional) Whitelist Entry Account of the user
     SwapSOLToken {
         input_sol_amount: u64,
         expected_spl_token_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Whitelists a single user by creating their entry PDA
+    /// (seeds: `["whitelistentry", whitelist global state, user]`).
+    /// A non-zero `allocation` overrides the global maximum contribution for this user.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority or admin
+    /// 1. `[]` Whitelist Global State Account
+    /// 2. `[writable]` Whitelist Entry Account to be created
+    /// 3. `[]` User account to be whitelisted
+    /// 4. `[]` System program
+    /// 5. `[writable, signer]` Funding account, pays for the entry account
+    /// 6. ..6+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    AddToWhitelist { allocation: u64 },
+
+    /// Removes a user from the whitelist by closing their entry PDA
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority or admin
+    /// 1. `[]` Whitelist Global State Account
+    /// 2. `[writable]` Whitelist Entry Account to be closed
+    /// 3. `[writable]` Account receiving the entry account's lamports
+    /// 4. ..4+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    RemoveFromWhitelist,
+
+    /// Delegates adding and removing whitelist entries to another key
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[]` New whitelist admin
+    /// 3. ..3+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    SetWhitelistAdmin,
+
+    /// Replaces the sale window and its phase schedule. Phases must be in ascending
+    /// order of `start_ts` and fall inside the sale window. Only allowed before anything
+    /// has been raised and before the sale has ended.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. ..2+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    SetSaleSchedule {
+        sale_start_ts: UnixTimestamp,
+        sale_end_ts: UnixTimestamp,
+        phases: Vec<SalePhase>,
+    },
+
+    /// Updates the sale price and rebinds the sale to a token swap pool. The pool's token
+    /// accounts must be owned by its swap authority, as in `InitWhiteList`, and the new pool
+    /// must pair the sale's Y mint with its quote mint. Pass the current accounts to only
+    /// update the price, which has to match the pool's constant price curve with
+    /// `is_fixed_price`. User records are left untouched.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[]` Token Swap Pool State account
+    /// 3. `[]` Y Token Mint account
+    /// 4. `[]` Y Token account
+    /// 5. `[]` Pool Quote Token account
+    /// 6. ..6+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    UpdateWhitelistConfig {
+        price_per_token_y: u64,
+        is_fixed_price: bool,
+    },
+
+    /// Pauses the sale, rejecting swaps until it is resumed. Refunds and claims stay open
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority or guardian
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. ..2+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    Pause,
+
+    /// Resumes a paused sale
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority or guardian
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. ..2+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    Resume,
+
+    /// Sets the guardian key allowed to pause and resume the sale
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[]` New guardian
+    /// 3. ..3+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    SetGuardian,
+
+    /// Proposes a new whitelist authority, which takes over once it accepts.
+    /// The whitelist creator used in the PDA seeds never changes.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[]` Proposed authority, a wallet or an SPL multisig
+    /// 3. ..3+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    ProposeAuthority,
+
+    /// Accepts a pending authority proposal
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Proposed authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. ..2+M `[signer]` M multisig signers, when the proposed authority is an SPL multisig
+    AcceptAuthority,
+
+    /// Closes the whitelist global state once the sale has ended, sending its rent
+    /// lamports to the destination and dropping the sale from the creator's registry.
+    /// The proceeds, in the global state itself or as pool tokens, have to be withdrawn first.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[writable]` Account receiving the rent lamports
+    /// 3. `[writable]` Creator Registry PDA Account of the whitelist creator
+    /// 4. `[]` Y Token account of the sale, the vault of a direct sale which has to be empty
+    /// 5. `[]` Token Swap State Account, for a pool sale only
+    /// 6. `[]` Pool Token Account owned by the Whitelist Global State PDA, which has to be
+    ///    empty, for a pool sale only
+    /// 7. ..7+M `[signer]` M multisig signers, when the authority is an SPL multisig,
+    ///    starting at 5 for a direct sale
+    CloseWhitelist,
+
+    /// Closes a user state once the sale has ended or the whitelist has been closed,
+    /// sending its rent lamports to the destination. While the whitelist is open, the
+    /// user must have no refund or escrowed tokens left to claim.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` User account
+    /// 1. `[writable]` Whitelist User State PDA Account
+    /// 2. `[]` Whitelist Global State Account
+    /// 3. `[writable]` Account receiving the rent lamports
+    CloseUserState,
+
+    /// Sets the treasury the sale proceeds are withdrawn to
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[]` New treasury
+    /// 3. ..3+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    SetTreasury,
+
+    /// Withdraws `amount` quote tokens from the pool by burning at most
+    /// `maximum_pool_token_amount` pool tokens held by the Whitelist Global State PDA,
+    /// and sends them to the treasury, unwrapped when the quote mint is wSOL.
+    /// Only allowed once the sale has ended.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[]` Token Swap State Account
+    /// 3. `[]` Token Swap Authority Account
+    /// 4. `[writable]` Pool Mint Account
+    /// 5. `[writable]` Pool Token Account owned by the Whitelist Global State PDA
+    /// 6. `[writable]` Pool Quote Token Account
+    /// 7. `[writable]` Pool Y Token Account
+    /// 8. `[writable]` For a wSOL sale, the Native SOL Associated Token Account of the Whitelist
+    ///    Global State PDA, closed to the treasury once the SOL has been withdrawn into it.
+    ///    Otherwise, the treasury's Associated Token Account of the quote mint.
+    /// 9. `[writable]` Pool Fee Account
+    /// 10. `[writable]` Treasury
+    /// 11. `[]` Token Program Account
+    /// 12. `[]` Token Swap Program Account
+    /// 13. ..13+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    WithdrawProceeds {
+        amount: u64,
+        maximum_pool_token_amount: u64,
+    },
+
+    /// Initializes a direct sale, where the PDA sells Y tokens from a vault it owns at a
+    /// fixed price and keeps the SOL paid, without going through a token swap pool.
+    /// The vault's key takes the place of the Y token account in the canonical PDA seeds.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Whitelist creator
+    /// 1. `[writable]` Whitelist Global State PDA Account
+    /// 2. `[]` Y Token Mint Account
+    /// 3. `[]` Y Token Vault Account, owned by the Whitelist Global State PDA
+    /// 4. `[]` System Program Account
+    /// 5. `[writable]` Creator Registry PDA Account, created on the creator's first sale
+    InitDirectSale {
+        price_per_token_y: u64,
+        merkle_root: [u8; 32],
+        min_contribution: u64,
+        max_contribution: u64,
+        sale_start_ts: UnixTimestamp,
+        sale_end_ts: UnixTimestamp,
+        sale_id: u64,
+    },
+
+    /// Buys `input_sol_amount / price_per_token_y` Y tokens from the vault of a direct sale,
+    /// `input_sol_amount` being a multiple of the price.
+    /// Membership is checked the same way as in `SwapSOLToken`. In a claim later sale the
+    /// tokens stay in the vault until they are claimed with `ClaimTokens`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account
+    /// 1. `[writable]` Whitelist User State PDA Account
+    /// 2. `[writable]` Whitelist Global State Account, receiving the SOL paid
+    /// 3. `[writable]` Y Token Vault Account
+    /// 4. `[writable]` User Y Token Account
+    /// 5. `[]` Token Program Account
+    /// 6. `[]` System Program Account
+    /// 7. `[optional]` Whitelist Entry PDA Account, checked instead of the Merkle proof
+    BuyDirect {
+        input_sol_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Withdraws `amount` lamports of direct sale proceeds held by the PDA to the treasury
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[writable]` Treasury
+    /// 3. ..3+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    WithdrawDirectProceeds { amount: u64 },
+
+    /// Sets the soft and hard caps on the total SOL raised (0 means no cap) and whether
+    /// the Y tokens bought are escrowed until the sale ends. Only allowed before anything
+    /// has been raised. A soft cap needs a claim later direct sale with an end time, so
+    /// that contributions can be refunded if it is missed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. ..2+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    SetSaleCaps {
+        soft_cap: u64,
+        hard_cap: u64,
+        is_claim_later: bool,
+    },
+
+    /// Refunds the user's contribution once the sale has ended below its soft cap
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account
+    /// 1. `[writable]` Whitelist User State PDA Account
+    /// 2. `[writable]` Whitelist Global State Account
+    Refund,
+
+    /// Claims the Y tokens a user bought in a claim later sale, once the sale has ended
+    /// and reached its soft cap
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` User account
+    /// 1. `[writable]` Whitelist User State PDA Account
+    /// 2. `[writable]` Whitelist Global State Account
+    /// 3. `[writable]` Y Token Vault Account
+    /// 4. `[writable]` User Y Token Account
+    /// 5. `[]` Token Program Account
+    ClaimTokens,
+
+    /// Locks the Y tokens bought in per user vesting vaults (seeds: `["whitelistvesting",
+    /// whitelist global state, user]`) instead of paying them out. `vesting_tge_unlock_bps`
+    /// of the tokens can be claimed right away with `ClaimVested`, the rest unlocks
+    /// linearly over `vesting_duration` seconds from `vesting_cliff_ts`. Only allowed
+    /// before anything has been raised, and not together with claim later.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. ..2+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    SetVestingSchedule {
+        is_vesting: bool,
+        vesting_cliff_ts: UnixTimestamp,
+        vesting_duration: i64,
+        vesting_tge_unlock_bps: u16,
+    },
+
+    /// Creates the user's vesting vault, a Y token account owned by the Whitelist Global
+    /// State PDA, which purchases of a vesting sale have to pay into
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, pays for the vault
+    /// 1. `[]` Whitelist Global State Account
+    /// 2. `[writable]` Vesting Vault PDA Account to be created
+    /// 3. `[]` Y Token Mint Account
+    /// 4. `[]` Token Program Account
+    /// 5. `[]` System Program Account
+    /// 6. `[]` Rent sysvar
+    CreateVestingVault,
+
+    /// Claims the Y tokens of the user's vesting vault unlocked so far
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` User account
+    /// 1. `[writable]` Whitelist User State PDA Account
+    /// 2. `[writable]` Whitelist Global State Account
+    /// 3. `[writable]` Vesting Vault PDA Account
+    /// 4. `[writable]` User Y Token Account
+    /// 5. `[]` Token Program Account
+    ClaimVested,
+
+    /// Buys in a single instruction: wraps `input_sol_amount` lamports of the user into their
+    /// native SOL associated account, swaps them the same way as `SwapSOLToken` with the user
+    /// as transfer authority. Missing associated accounts are created, at the user's expense,
+    /// and a native SOL account created this way is closed again once the swap is done.
+    /// In a vesting sale the Y tokens go to the user's vesting vault,
+    /// which has to be created with `CreateVestingVault` first. Only for sales quoted in wSOL.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` User account
+    ///   1. `[writable]` Whitelist User State PDA Account
+    ///   2. `[writable]` Whitelist Global State Account
+    ///   3. `[]` Token Swap State Account
+    ///   4. `[]` Swap authority PDA Account
+    ///   5. `[writable]` User Native SOL Associated Token Account
+    ///   6. `[writable]` User Y Associated Token Account, or Vesting Vault PDA Account
+    ///   7. `[]` Y Token Mint Account
+    ///   8. `[writable]` Token Swap Pool Native Sol Token Account
+    ///   9. `[writable]` Token Swap (**Token Y**) Token Account
+    ///  10. `[writable]` Pool Mint Token
+    ///  11. `[writable]` Pool Token Fee Account
+    ///  12. `[writable]` Host fee account to receive additional trading fees
+    ///  13. `[]` Token program id
+    ///  14. `[]` Token Swap program id
+    ///  15. `[]` System program
+    ///  16. `[]` Associated Token program
+    ///  17. `[]` Rent sysvar
+    ///  18. `[]` Native SOL Mint Account
+    ///  19. `[]` (Optional) Whitelist Entry Account of the user
+    BuyWithSOL {
+        input_sol_amount: u64,
+        expected_spl_token_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
     },
+
+    /// Migrates a global state account created before its layout had a version byte
+    /// (version 1) to the current layout, growing it in place. Anyone can migrate a sale,
+    /// the funding account paying for the extra rent. The sale keeps its address, the
+    /// creator becomes its authority and its allowed addresses a Merkle allowlist. As in
+    /// version 1, each user can buy only once, into an empty Y token account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Funding account
+    /// 1. `[writable]` Whitelist Global State Account
+    /// 2. `[]` System program
+    MigrateGlobalState,
+
+    /// Withdraws `amount` Y tokens left unsold in the vault of a direct sale once the sale
+    /// has ended. The tokens escrowed for the buyers stay in the vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Whitelist authority
+    /// 1. `[]` Whitelist Global State Account
+    /// 2. `[writable]` Y Token vault
+    /// 3. `[writable]` Y Token account receiving the unsold tokens
+    /// 4. `[]` Token Program Account
+    /// 5. ..5+M `[signer]` M multisig signers, when the authority is an SPL multisig
+    WithdrawUnsoldTokens { amount: u64 },
 }
 
+/// Version byte prefixed to every packed instruction
+pub const INSTRUCTION_VERSION: u8 = 1;
+
 impl WhiteListInstruction {
-    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
-        if input.len() >= 32 {
-            let (key, rest) = input.split_at(32);
-            let pk = Pubkey::new(key);
-            Ok((pk, rest))
-        } else {
-            Err(InvalidInstruction.into())
+    /// Unpacks a version prefixed, Borsh encoded instruction. Short, trailing or
+    /// otherwise malformed data is rejected with `InvalidInstruction`.
+    pub fn parse_instruction(instruction_data: &[u8]) -> Result<Self, ProgramError> {
+        let (version, rest) = instruction_data.split_first().ok_or(InvalidInstruction)?;
+
+        if *version != INSTRUCTION_VERSION {
+            msg!("Whitelist: Unsupported instruction version {}", version);
+            return Err(InvalidInstruction.into());
         }
+
+        Self::try_from_slice(rest).map_err(|_| InvalidInstruction.into())
     }
 
-    fn parse_amount(amount_u8_data: &[u8]) -> Result<u64, ProgramError> {
-        let amount = amount_u8_data
-            .get(..8)
-            .and_then(|slice| slice.try_into().ok())
-            .map(u64::from_le_bytes)
-            .ok_or(ProgramError::InvalidAccountData)?;
+    /// Packs the instruction into a version prefixed, Borsh encoded byte buffer
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![INSTRUCTION_VERSION];
+        // Serializing into a Vec can't fail
+        buf.extend_from_slice(&self.try_to_vec().unwrap());
+        buf
+    }
+}
 
-        Ok(amount)
+// Puts the authority account first and appends its multisig signers, if any
+fn push_authority_accounts(
+    accounts: &mut Vec<AccountMeta>,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) {
+    accounts.insert(
+        0,
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    );
+    for signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer, true));
     }
+}
 
-    fn parse_authorized_addresses(
-        addresses_byte_data_array: &[u8],
-    ) -> Result<Vec<Pubkey>, ProgramError> {
-        let mut pub_keys: Vec<Pubkey> = vec![];
-        let mut rest_data: &[u8] = addresses_byte_data_array;
+// The Y token account a purchase pays into, the user's vesting vault in a vesting sale
+fn user_y_token_account(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_mint: &Pubkey,
+    use_vesting_vault: bool,
+) -> Pubkey {
+    if use_vesting_vault {
+        find_whitelist_vesting_vault_address(whitelist_global_state, user).0
+    } else {
+        get_associated_token_address(user, y_mint)
+    }
+}
 
-        for _ in [0, 1, 2, 3, 4, 5].iter() {
-            let (address, rest) = Self::unpack_pubkey(rest_data)?;
-            pub_keys.push(address);
-            rest_data = rest;
-        }
+/// Creates an `InitWhiteList` instruction, deriving the whitelist global state PDA
+pub fn init_whitelist(
+    whitelist_creator: &Pubkey,
+    token_swap_pool_state: &Pubkey,
+    y_mint: &Pubkey,
+    y_token_account: &Pubkey,
+    quote_token_account: &Pubkey,
+    price_per_token_y: u64,
+    merkle_root: [u8; 32],
+    min_contribution: u64,
+    max_contribution: u64,
+    sale_start_ts: UnixTimestamp,
+    sale_end_ts: UnixTimestamp,
+    is_fixed_price: bool,
+    sale_id: u64,
+) -> Instruction {
+    let (whitelist_global_state, _) =
+        find_whitelist_address(whitelist_creator, y_token_account, sale_id);
+    let (whitelist_registry, _) = find_whitelist_registry_address(whitelist_creator);
 
-        Ok(pub_keys)
+    let data = WhiteListInstruction::InitWhiteList {
+        price_per_token_y,
+        merkle_root,
+        min_contribution,
+        max_contribution,
+        sale_start_ts,
+        sale_end_ts,
+        is_fixed_price,
+        sale_id,
     }
+    .pack();
 
-    pub fn parse_instruction(instruction_data: &[u8]) -> Result<Self, ProgramError> {
-        let (tag, rest) = instruction_data.split_first().ok_or(InvalidInstruction)?;
+    let accounts = vec![
+        AccountMeta::new(*whitelist_creator, true),
+        AccountMeta::new(whitelist_global_state, false),
+        AccountMeta::new_readonly(*token_swap_pool_state, false),
+        AccountMeta::new_readonly(*y_mint, false),
+        AccountMeta::new_readonly(*y_token_account, false),
+        AccountMeta::new_readonly(*quote_token_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(whitelist_registry, false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
 
-        match tag {
-            0 => Ok(WhiteListInstruction::InitWhiteList {
-                whitelist_pda_bump: *rest.split_first().unwrap().0,
-                price_per_token_y: Self::parse_amount(rest.get(1..9).unwrap())?,
-                authorized_addresses: Self::parse_authorized_addresses(rest.get(9..).unwrap())?,
-            }),
+/// Creates a `CreateAndWrapSOLToken` instruction for the funding account's native SOL ATA.
+/// Passing the whitelist global state makes the wrap fail while the sale is paused.
+pub fn create_and_wrap_sol_token(
+    funding_account: &Pubkey,
+    amount_to_be_wrapped: u64,
+    whitelist_global_state: Option<&Pubkey>,
+) -> Instruction {
+    let data = WhiteListInstruction::CreateAndWrapSOLToken {
+        amount_to_be_wrapped,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(
+            get_associated_token_address(funding_account, &native_mint::id()),
+            false,
+        ),
+        AccountMeta::new_readonly(native_mint::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    if let Some(whitelist_global_state) = whitelist_global_state {
+        accounts.push(AccountMeta::new_readonly(*whitelist_global_state, false));
+    }
 
-            1 => Ok(WhiteListInstruction::CreateAndWrapSOLToken {
-                amount_to_be_wrapped: Self::parse_amount(rest)?,
-            }),
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WrapSOLToken` instruction for the funding account's native SOL ATA.
+/// Passing the whitelist global state makes the wrap fail while the sale is paused.
+pub fn wrap_sol_token(
+    funding_account: &Pubkey,
+    amount_to_be_wrapped: u64,
+    whitelist_global_state: Option<&Pubkey>,
+) -> Instruction {
+    let data = WhiteListInstruction::WrapSOLToken {
+        amount_to_be_wrapped,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(
+            get_associated_token_address(funding_account, &native_mint::id()),
+            false,
+        ),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    if let Some(whitelist_global_state) = whitelist_global_state {
+        accounts.push(AccountMeta::new_readonly(*whitelist_global_state, false));
+    }
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `UnwrapSOLToken` instruction closing the owner's native SOL ATA
+pub fn unwrap_sol_token(owner: &Pubkey) -> Instruction {
+    let data = WhiteListInstruction::UnwrapSOLToken.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(
+            get_associated_token_address(owner, &native_mint::id()),
+            false,
+        ),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SwapSOLToken` instruction, swapping from the user's ATA of the sale's quote
+/// mint into their Y token ATA, or into their vesting vault with `use_vesting_vault`. With
+/// `use_whitelist_entry` the user's entry PDA is passed and `allocation` and `proof`
+/// are ignored by the program.
+pub fn swap_sol_token(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_mint: &Pubkey,
+    quote_mint: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    token_swap_pool_state: &Pubkey,
+    pool_quote_token_account: &Pubkey,
+    pool_y_token_account: &Pubkey,
+    pool_mint: &Pubkey,
+    pool_fee_account: &Pubkey,
+    host_fee_account: &Pubkey,
+    input_sol_amount: u64,
+    expected_spl_token_amount: u64,
+    allocation: u64,
+    proof: Vec<[u8; 32]>,
+    use_whitelist_entry: bool,
+    use_vesting_vault: bool,
+) -> Instruction {
+    let (whitelist_user_state, _) = find_whitelist_user_state_address(whitelist_global_state, user);
+    let user_y_token_account =
+        user_y_token_account(user, whitelist_global_state, y_mint, use_vesting_vault);
+    let (swap_authority, _) =
+        Pubkey::find_program_address(&[&token_swap_pool_state.to_bytes()], &spl_token_swap::id());
+
+    let data = WhiteListInstruction::SwapSOLToken {
+        input_sol_amount,
+        expected_spl_token_amount,
+        allocation,
+        proof,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(whitelist_user_state, false),
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*token_swap_pool_state, false),
+        AccountMeta::new_readonly(swap_authority, false),
+        AccountMeta::new_readonly(*user_transfer_authority, true),
+        AccountMeta::new(get_associated_token_address(user, quote_mint), false),
+        AccountMeta::new(user_y_token_account, false),
+        AccountMeta::new(*pool_quote_token_account, false),
+        AccountMeta::new(*pool_y_token_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new(*pool_fee_account, false),
+        AccountMeta::new(*host_fee_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_token_swap::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if use_whitelist_entry {
+        let (whitelist_entry, _) = find_whitelist_entry_address(whitelist_global_state, user);
+        accounts.push(AccountMeta::new_readonly(whitelist_entry, false));
+    }
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
 
-            2 => Ok(WhiteListInstruction::WrapSOLToken {
-                amount_to_be_wrapped: Self::parse_amount(rest)?,
-            }),
+/// Creates an `AddToWhitelist` instruction, deriving the user's entry PDA
+pub fn add_to_whitelist(
+    admin: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+    funding_account: &Pubkey,
+    allocation: u64,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let (whitelist_entry, _) = find_whitelist_entry_address(whitelist_global_state, user);
 
-            3 => Ok(WhiteListInstruction::UnwrapSOLToken),
+    let data = WhiteListInstruction::AddToWhitelist { allocation }.pack();
 
-            4 => Ok(WhiteListInstruction::SwapSOLToken {
-                input_sol_amount: Self::parse_amount(rest.get(0..8).unwrap())?,
-                expected_spl_token_amount: Self::parse_amount(rest.get(8..).unwrap())?,
-            }),
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*whitelist_global_state, false),
+        AccountMeta::new(whitelist_entry, false),
+        AccountMeta::new_readonly(*user, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*funding_account, true),
+    ];
+    push_authority_accounts(&mut accounts, admin, multisig_signers);
 
-            _ => return Err(InvalidInstruction.into()),
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `RemoveFromWhitelist` instruction, deriving the user's entry PDA
+pub fn remove_from_whitelist(
+    admin: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+    destination: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let (whitelist_entry, _) = find_whitelist_entry_address(whitelist_global_state, user);
+
+    let data = WhiteListInstruction::RemoveFromWhitelist.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*whitelist_global_state, false),
+        AccountMeta::new(whitelist_entry, false),
+        AccountMeta::new(*destination, false),
+    ];
+    push_authority_accounts(&mut accounts, admin, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetWhitelistAdmin` instruction
+pub fn set_whitelist_admin(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    new_admin: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::SetWhitelistAdmin.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*new_admin, false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetSaleSchedule` instruction
+pub fn set_sale_schedule(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    sale_start_ts: UnixTimestamp,
+    sale_end_ts: UnixTimestamp,
+    phases: Vec<SalePhase>,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::SetSaleSchedule {
+        sale_start_ts,
+        sale_end_ts,
+        phases,
+    }
+    .pack();
+
+    let mut accounts = vec![AccountMeta::new(*whitelist_global_state, false)];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `UpdateWhitelistConfig` instruction
+pub fn update_whitelist_config(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    token_swap_pool_state: &Pubkey,
+    y_mint: &Pubkey,
+    y_token_account: &Pubkey,
+    quote_token_account: &Pubkey,
+    price_per_token_y: u64,
+    is_fixed_price: bool,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::UpdateWhitelistConfig {
+        price_per_token_y,
+        is_fixed_price,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*token_swap_pool_state, false),
+        AccountMeta::new_readonly(*y_mint, false),
+        AccountMeta::new_readonly(*y_token_account, false),
+        AccountMeta::new_readonly(*quote_token_account, false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `Pause` instruction
+pub fn pause(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::Pause.pack();
+
+    let mut accounts = vec![AccountMeta::new(*whitelist_global_state, false)];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `Resume` instruction
+pub fn resume(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::Resume.pack();
+
+    let mut accounts = vec![AccountMeta::new(*whitelist_global_state, false)];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetGuardian` instruction
+pub fn set_guardian(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    guardian: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::SetGuardian.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*guardian, false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ProposeAuthority` instruction
+pub fn propose_authority(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    proposed_authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::ProposeAuthority.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*proposed_authority, false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `AcceptAuthority` instruction
+pub fn accept_authority(
+    proposed_authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::AcceptAuthority.pack();
+
+    let mut accounts = vec![AccountMeta::new(*whitelist_global_state, false)];
+    push_authority_accounts(&mut accounts, proposed_authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CloseWhitelist` instruction, deriving the registry of the whitelist creator.
+/// A pool sale passes its token swap state and the whitelist's pool token account.
+pub fn close_whitelist(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    whitelist_creator: &Pubkey,
+    y_token_account: &Pubkey,
+    pool_accounts: Option<(&Pubkey, &Pubkey)>,
+    destination: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let (whitelist_registry, _) = find_whitelist_registry_address(whitelist_creator);
+
+    let data = WhiteListInstruction::CloseWhitelist.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new(whitelist_registry, false),
+        AccountMeta::new_readonly(*y_token_account, false),
+    ];
+    if let Some((token_swap_pool_state, pool_token_account)) = pool_accounts {
+        accounts.push(AccountMeta::new_readonly(*token_swap_pool_state, false));
+        accounts.push(AccountMeta::new_readonly(*pool_token_account, false));
+    }
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CloseUserState` instruction, deriving the user's state PDA
+pub fn close_user_state(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let (whitelist_user_state, _) = find_whitelist_user_state_address(whitelist_global_state, user);
+
+    let data = WhiteListInstruction::CloseUserState.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new(whitelist_user_state, false),
+        AccountMeta::new_readonly(*whitelist_global_state, false),
+        AccountMeta::new(*destination, false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetTreasury` instruction
+pub fn set_treasury(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    treasury: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::SetTreasury.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*treasury, false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WithdrawProceeds` instruction. For a wSOL sale, the native SOL associated token
+/// account of the whitelist global state must exist, e.g. created in the same transaction,
+/// otherwise the treasury's associated token account of the quote mint must.
+pub fn withdraw_proceeds(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    token_swap_pool_state: &Pubkey,
+    pool_mint: &Pubkey,
+    pool_token_account: &Pubkey,
+    pool_quote_token_account: &Pubkey,
+    pool_y_token_account: &Pubkey,
+    pool_fee_account: &Pubkey,
+    treasury: &Pubkey,
+    quote_mint: &Pubkey,
+    amount: u64,
+    maximum_pool_token_amount: u64,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let (swap_authority, _) =
+        Pubkey::find_program_address(&[&token_swap_pool_state.to_bytes()], &spl_token_swap::id());
+    let proceeds_token_account = if quote_mint.eq(&native_mint::id()) {
+        get_associated_token_address(whitelist_global_state, quote_mint)
+    } else {
+        get_associated_token_address(treasury, quote_mint)
+    };
+
+    let data = WhiteListInstruction::WithdrawProceeds {
+        amount,
+        maximum_pool_token_amount,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*token_swap_pool_state, false),
+        AccountMeta::new_readonly(swap_authority, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new(*pool_token_account, false),
+        AccountMeta::new(*pool_quote_token_account, false),
+        AccountMeta::new(*pool_y_token_account, false),
+        AccountMeta::new(proceeds_token_account, false),
+        AccountMeta::new(*pool_fee_account, false),
+        AccountMeta::new(*treasury, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_token_swap::id(), false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `InitDirectSale` instruction, deriving the whitelist global state PDA from the vault
+pub fn init_direct_sale(
+    whitelist_creator: &Pubkey,
+    y_mint: &Pubkey,
+    y_vault: &Pubkey,
+    price_per_token_y: u64,
+    merkle_root: [u8; 32],
+    min_contribution: u64,
+    max_contribution: u64,
+    sale_start_ts: UnixTimestamp,
+    sale_end_ts: UnixTimestamp,
+    sale_id: u64,
+) -> Instruction {
+    let (whitelist_global_state, _) = find_whitelist_address(whitelist_creator, y_vault, sale_id);
+    let (whitelist_registry, _) = find_whitelist_registry_address(whitelist_creator);
+
+    let data = WhiteListInstruction::InitDirectSale {
+        price_per_token_y,
+        merkle_root,
+        min_contribution,
+        max_contribution,
+        sale_start_ts,
+        sale_end_ts,
+        sale_id,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*whitelist_creator, true),
+        AccountMeta::new(whitelist_global_state, false),
+        AccountMeta::new_readonly(*y_mint, false),
+        AccountMeta::new_readonly(*y_vault, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(whitelist_registry, false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `BuyDirect` instruction, paying into the user's Y token ATA, or into their
+/// vesting vault with `use_vesting_vault`. With `use_whitelist_entry` the user's entry
+/// PDA is passed and `allocation` and `proof` are ignored by the program.
+pub fn buy_direct(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_mint: &Pubkey,
+    y_vault: &Pubkey,
+    input_sol_amount: u64,
+    allocation: u64,
+    proof: Vec<[u8; 32]>,
+    use_whitelist_entry: bool,
+    use_vesting_vault: bool,
+) -> Instruction {
+    let (whitelist_user_state, _) = find_whitelist_user_state_address(whitelist_global_state, user);
+    let user_y_token_account =
+        user_y_token_account(user, whitelist_global_state, y_mint, use_vesting_vault);
+
+    let data = WhiteListInstruction::BuyDirect {
+        input_sol_amount,
+        allocation,
+        proof,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(whitelist_user_state, false),
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new(*y_vault, false),
+        AccountMeta::new(user_y_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if use_whitelist_entry {
+        let (whitelist_entry, _) = find_whitelist_entry_address(whitelist_global_state, user);
+        accounts.push(AccountMeta::new_readonly(whitelist_entry, false));
+    }
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WithdrawDirectProceeds` instruction
+pub fn withdraw_direct_proceeds(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    treasury: &Pubkey,
+    amount: u64,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::WithdrawDirectProceeds { amount }.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new(*treasury, false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetSaleCaps` instruction
+pub fn set_sale_caps(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    soft_cap: u64,
+    hard_cap: u64,
+    is_claim_later: bool,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::SetSaleCaps {
+        soft_cap,
+        hard_cap,
+        is_claim_later,
+    }
+    .pack();
+
+    let mut accounts = vec![AccountMeta::new(*whitelist_global_state, false)];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `Refund` instruction, deriving the user's state PDA
+pub fn refund(user: &Pubkey, whitelist_global_state: &Pubkey) -> Instruction {
+    let (whitelist_user_state, _) = find_whitelist_user_state_address(whitelist_global_state, user);
+
+    let data = WhiteListInstruction::Refund.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(whitelist_user_state, false),
+        AccountMeta::new(*whitelist_global_state, false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ClaimTokens` instruction, paying into the user's Y token ATA
+pub fn claim_tokens(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_mint: &Pubkey,
+    y_vault: &Pubkey,
+) -> Instruction {
+    let (whitelist_user_state, _) = find_whitelist_user_state_address(whitelist_global_state, user);
+
+    let data = WhiteListInstruction::ClaimTokens.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new(whitelist_user_state, false),
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new(*y_vault, false),
+        AccountMeta::new(get_associated_token_address(user, y_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `SetVestingSchedule` instruction
+pub fn set_vesting_schedule(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    is_vesting: bool,
+    vesting_cliff_ts: UnixTimestamp,
+    vesting_duration: i64,
+    vesting_tge_unlock_bps: u16,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::SetVestingSchedule {
+        is_vesting,
+        vesting_cliff_ts,
+        vesting_duration,
+        vesting_tge_unlock_bps,
+    }
+    .pack();
+
+    let mut accounts = vec![AccountMeta::new(*whitelist_global_state, false)];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CreateVestingVault` instruction, deriving the user's vesting vault PDA
+pub fn create_vesting_vault(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_mint: &Pubkey,
+) -> Instruction {
+    let (vesting_vault, _) = find_whitelist_vesting_vault_address(whitelist_global_state, user);
+
+    let data = WhiteListInstruction::CreateVestingVault.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(*whitelist_global_state, false),
+        AccountMeta::new(vesting_vault, false),
+        AccountMeta::new_readonly(*y_mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `ClaimVested` instruction, paying into the user's Y token ATA
+pub fn claim_vested(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_mint: &Pubkey,
+) -> Instruction {
+    let (whitelist_user_state, _) = find_whitelist_user_state_address(whitelist_global_state, user);
+    let (vesting_vault, _) = find_whitelist_vesting_vault_address(whitelist_global_state, user);
+
+    let data = WhiteListInstruction::ClaimVested.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new(whitelist_user_state, false),
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new(vesting_vault, false),
+        AccountMeta::new(get_associated_token_address(user, y_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `BuyWithSOL` instruction, wrapping, swapping and unwrapping in one go. With
+/// `use_whitelist_entry` the user's entry PDA is passed and `allocation` and `proof` are
+/// ignored by the program.
+pub fn buy_with_sol(
+    user: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_mint: &Pubkey,
+    token_swap_pool_state: &Pubkey,
+    pool_native_sol_token_account: &Pubkey,
+    pool_y_token_account: &Pubkey,
+    pool_mint: &Pubkey,
+    pool_fee_account: &Pubkey,
+    host_fee_account: &Pubkey,
+    input_sol_amount: u64,
+    expected_spl_token_amount: u64,
+    allocation: u64,
+    proof: Vec<[u8; 32]>,
+    use_whitelist_entry: bool,
+    use_vesting_vault: bool,
+) -> Instruction {
+    let (whitelist_user_state, _) = find_whitelist_user_state_address(whitelist_global_state, user);
+    let user_y_token_account =
+        user_y_token_account(user, whitelist_global_state, y_mint, use_vesting_vault);
+    let (swap_authority, _) =
+        Pubkey::find_program_address(&[&token_swap_pool_state.to_bytes()], &spl_token_swap::id());
+
+    let data = WhiteListInstruction::BuyWithSOL {
+        input_sol_amount,
+        expected_spl_token_amount,
+        allocation,
+        proof,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(whitelist_user_state, false),
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(*token_swap_pool_state, false),
+        AccountMeta::new_readonly(swap_authority, false),
+        AccountMeta::new(
+            get_associated_token_address(user, &native_mint::id()),
+            false,
+        ),
+        AccountMeta::new(user_y_token_account, false),
+        AccountMeta::new_readonly(*y_mint, false),
+        AccountMeta::new(*pool_native_sol_token_account, false),
+        AccountMeta::new(*pool_y_token_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new(*pool_fee_account, false),
+        AccountMeta::new(*host_fee_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_token_swap::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(native_mint::id(), false),
+    ];
+    if use_whitelist_entry {
+        let (whitelist_entry, _) = find_whitelist_entry_address(whitelist_global_state, user);
+        accounts.push(AccountMeta::new_readonly(whitelist_entry, false));
+    }
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `MigrateGlobalState` instruction
+pub fn migrate_global_state(
+    funding_account: &Pubkey,
+    whitelist_global_state: &Pubkey,
+) -> Instruction {
+    let data = WhiteListInstruction::MigrateGlobalState.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(*whitelist_global_state, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `WithdrawUnsoldTokens` instruction
+pub fn withdraw_unsold_tokens(
+    authority: &Pubkey,
+    whitelist_global_state: &Pubkey,
+    y_vault: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+    multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let data = WhiteListInstruction::WithdrawUnsoldTokens { amount }.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*whitelist_global_state, false),
+        AccountMeta::new(*y_vault, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    push_authority_accounts(&mut accounts, authority, multisig_signers);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_init_whitelist() {
+        let price_per_token_y: u64 = 10_000;
+        let merkle_root = [7u8; 32];
+        let min_contribution: u64 = 1;
+        let max_contribution: u64 = 5_000_000;
+        let sale_start_ts: UnixTimestamp = 1_600_000_000;
+        let sale_end_ts: UnixTimestamp = 1_700_000_000;
+        let sale_id: u64 = 2;
+        let check = WhiteListInstruction::InitWhiteList {
+            price_per_token_y,
+            merkle_root,
+            min_contribution,
+            max_contribution,
+            sale_start_ts,
+            sale_end_ts,
+            is_fixed_price: true,
+            sale_id,
+        };
+        let packed = check.pack();
+        let mut expect = vec![INSTRUCTION_VERSION, 0u8];
+        expect.extend_from_slice(&price_per_token_y.to_le_bytes());
+        expect.extend_from_slice(&merkle_root);
+        expect.extend_from_slice(&min_contribution.to_le_bytes());
+        expect.extend_from_slice(&max_contribution.to_le_bytes());
+        expect.extend_from_slice(&sale_start_ts.to_le_bytes());
+        expect.extend_from_slice(&sale_end_ts.to_le_bytes());
+        expect.push(1u8);
+        expect.extend_from_slice(&sale_id.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = WhiteListInstruction::parse_instruction(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn pack_swap_sol_token() {
+        let input_sol_amount: u64 = 100;
+        let expected_spl_token_amount: u64 = 10;
+        let allocation: u64 = 1_000;
+        let proof = vec![[1u8; 32], [2u8; 32]];
+        let check = WhiteListInstruction::SwapSOLToken {
+            input_sol_amount,
+            expected_spl_token_amount,
+            allocation,
+            proof: proof.clone(),
+        };
+        let packed = check.pack();
+        let mut expect = vec![INSTRUCTION_VERSION, 4u8];
+        expect.extend_from_slice(&input_sol_amount.to_le_bytes());
+        expect.extend_from_slice(&expected_spl_token_amount.to_le_bytes());
+        expect.extend_from_slice(&allocation.to_le_bytes());
+        expect.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+        proof.iter().for_each(|node| expect.extend_from_slice(node));
+        assert_eq!(packed, expect);
+        let unpacked = WhiteListInstruction::parse_instruction(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn round_trip_all_instructions() {
+        let instructions = vec![
+            WhiteListInstruction::CreateAndWrapSOLToken {
+                amount_to_be_wrapped: 5,
+            },
+            WhiteListInstruction::WrapSOLToken {
+                amount_to_be_wrapped: 6,
+            },
+            WhiteListInstruction::UnwrapSOLToken,
+            WhiteListInstruction::AddToWhitelist { allocation: 7 },
+            WhiteListInstruction::RemoveFromWhitelist,
+            WhiteListInstruction::SetWhitelistAdmin,
+            WhiteListInstruction::SetSaleSchedule {
+                sale_start_ts: 10,
+                sale_end_ts: 20,
+                phases: vec![SalePhase {
+                    start_ts: 15,
+                    price_per_token_y: 3,
+                    max_contribution: 4,
+                    is_public: true,
+                }],
+            },
+            WhiteListInstruction::UpdateWhitelistConfig {
+                price_per_token_y: 8,
+                is_fixed_price: false,
+            },
+            WhiteListInstruction::Pause,
+            WhiteListInstruction::Resume,
+            WhiteListInstruction::SetGuardian,
+            WhiteListInstruction::ProposeAuthority,
+            WhiteListInstruction::AcceptAuthority,
+            WhiteListInstruction::CloseWhitelist,
+            WhiteListInstruction::CloseUserState,
+            WhiteListInstruction::SetTreasury,
+            WhiteListInstruction::WithdrawProceeds {
+                amount: 9,
+                maximum_pool_token_amount: 10,
+            },
+            WhiteListInstruction::InitDirectSale {
+                price_per_token_y: 11,
+                merkle_root: [12u8; 32],
+                min_contribution: 13,
+                max_contribution: 14,
+                sale_start_ts: 15,
+                sale_end_ts: 16,
+                sale_id: 3,
+            },
+            WhiteListInstruction::BuyDirect {
+                input_sol_amount: 17,
+                allocation: 18,
+                proof: vec![[19u8; 32]],
+            },
+            WhiteListInstruction::WithdrawDirectProceeds { amount: 20 },
+            WhiteListInstruction::SetSaleCaps {
+                soft_cap: 21,
+                hard_cap: 22,
+                is_claim_later: true,
+            },
+            WhiteListInstruction::Refund,
+            WhiteListInstruction::ClaimTokens,
+            WhiteListInstruction::SetVestingSchedule {
+                is_vesting: true,
+                vesting_cliff_ts: 23,
+                vesting_duration: 24,
+                vesting_tge_unlock_bps: 2_500,
+            },
+            WhiteListInstruction::CreateVestingVault,
+            WhiteListInstruction::ClaimVested,
+            WhiteListInstruction::BuyWithSOL {
+                input_sol_amount: 25,
+                expected_spl_token_amount: 26,
+                allocation: 27,
+                proof: vec![[28u8; 32]],
+            },
+            WhiteListInstruction::MigrateGlobalState,
+            WhiteListInstruction::WithdrawUnsoldTokens { amount: 29 },
+        ];
+
+        for check in instructions {
+            let packed = check.pack();
+            let unpacked = WhiteListInstruction::parse_instruction(&packed).unwrap();
+            assert_eq!(unpacked, check);
         }
     }
+
+    #[test]
+    fn parse_malformed_instructions() {
+        let packed = WhiteListInstruction::SwapSOLToken {
+            input_sol_amount: 100,
+            expected_spl_token_amount: 10,
+            allocation: 0,
+            proof: vec![[1u8; 32]],
+        }
+        .pack();
+
+        let invalid_instruction = Err(InvalidInstruction.into());
+
+        // Empty data
+        assert_eq!(
+            WhiteListInstruction::parse_instruction(&[]),
+            invalid_instruction
+        );
+
+        // Unknown version
+        let mut wrong_version = packed.clone();
+        wrong_version[0] = INSTRUCTION_VERSION + 1;
+        assert_eq!(
+            WhiteListInstruction::parse_instruction(&wrong_version),
+            invalid_instruction
+        );
+
+        // Truncated data
+        assert_eq!(
+            WhiteListInstruction::parse_instruction(&packed[..packed.len() - 1]),
+            invalid_instruction
+        );
+
+        // Trailing data
+        let mut trailing = packed;
+        trailing.push(0);
+        assert_eq!(
+            WhiteListInstruction::parse_instruction(&trailing),
+            invalid_instruction
+        );
+
+        // Unknown tag
+        assert_eq!(
+            WhiteListInstruction::parse_instruction(&[INSTRUCTION_VERSION, 255]),
+            invalid_instruction
+        );
+    }
 }
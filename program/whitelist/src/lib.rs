@@ -1,9 +1,135 @@
 pub mod error;
+pub mod event;
 pub mod instruction;
+pub mod merkle;
 pub mod processor;
 pub mod state;
 
 #[cfg(not(feature = "no-entrypoint"))]
 mod entrypoint;
 
+use solana_program::pubkey::Pubkey;
+
 solana_program::declare_id!("FSeLPB3DLwMfnQr6oG4YX9cdmGaZ2M99wCDt7TgGbzoF");
+
+pub const WHITELIST_PDA_SEED: &[u8] = b"whitelistpda";
+pub const WHITELIST_USER_SEED: &[u8] = b"whitelistuser";
+pub const WHITELIST_ENTRY_SEED: &[u8] = b"whitelistentry";
+pub const WHITELIST_VESTING_SEED: &[u8] = b"whitelistvesting";
+pub const WHITELIST_REGISTRY_SEED: &[u8] = b"whitelistregistry";
+
+/// Derives the whitelist global state address of the creator's sale `sale_id` of the given
+/// Y token account, along with the canonical bump the program requires
+pub fn find_whitelist_address(
+    whitelist_creator: &Pubkey,
+    y_token_account: &Pubkey,
+    sale_id: u64,
+) -> (Pubkey, u8) {
+    find_whitelist_program_address(whitelist_creator, y_token_account, sale_id, &id())
+}
+
+/// Same as `find_whitelist_address`, for the program deployed at `program_id`
+pub fn find_whitelist_program_address(
+    whitelist_creator: &Pubkey,
+    y_token_account: &Pubkey,
+    sale_id: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WHITELIST_PDA_SEED,
+            &whitelist_creator.to_bytes(),
+            &y_token_account.to_bytes(),
+            &sale_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the address of the registry listing the sales a creator has running
+pub fn find_whitelist_registry_address(whitelist_creator: &Pubkey) -> (Pubkey, u8) {
+    find_whitelist_registry_program_address(whitelist_creator, &id())
+}
+
+/// Same as `find_whitelist_registry_address`, for the program deployed at `program_id`
+pub fn find_whitelist_registry_program_address(
+    whitelist_creator: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[WHITELIST_REGISTRY_SEED, &whitelist_creator.to_bytes()],
+        program_id,
+    )
+}
+
+/// Derives the state address of a user taking part in the given whitelist sale
+pub fn find_whitelist_user_state_address(
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+) -> (Pubkey, u8) {
+    find_whitelist_user_state_program_address(whitelist_global_state, user, &id())
+}
+
+/// Same as `find_whitelist_user_state_address`, for the program deployed at `program_id`
+pub fn find_whitelist_user_state_program_address(
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WHITELIST_USER_SEED,
+            &whitelist_global_state.to_bytes(),
+            &user.to_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the entry address of a user added to the given whitelist with `AddToWhitelist`
+pub fn find_whitelist_entry_address(
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+) -> (Pubkey, u8) {
+    find_whitelist_entry_program_address(whitelist_global_state, user, &id())
+}
+
+/// Same as `find_whitelist_entry_address`, for the program deployed at `program_id`
+pub fn find_whitelist_entry_program_address(
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WHITELIST_ENTRY_SEED,
+            &whitelist_global_state.to_bytes(),
+            &user.to_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the vesting vault address holding the Y tokens a user bought in a vesting sale
+pub fn find_whitelist_vesting_vault_address(
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+) -> (Pubkey, u8) {
+    find_whitelist_vesting_vault_program_address(whitelist_global_state, user, &id())
+}
+
+/// Same as `find_whitelist_vesting_vault_address`, for the program deployed at `program_id`
+pub fn find_whitelist_vesting_vault_program_address(
+    whitelist_global_state: &Pubkey,
+    user: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WHITELIST_VESTING_SEED,
+            &whitelist_global_state.to_bytes(),
+            &user.to_bytes(),
+        ],
+        program_id,
+    )
+}
@@ -0,0 +1,285 @@
+use solana_program::{hash::hashv, pubkey::Pubkey};
+use std::{collections::HashSet, str::FromStr};
+use thiserror::Error;
+
+// Domain separation prefixes so that a leaf can never be passed off as an internal node
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hashes a single allowlist entry. An `allocation` of 0 means the user has no per-user cap.
+pub fn leaf_hash(user: &Pubkey, allocation: u64) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, user.as_ref(), &allocation.to_le_bytes()]).to_bytes()
+}
+
+/// Hashes two sibling nodes. The pair is sorted first, so proofs don't need direction bits.
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Verifies that `leaf` is part of the tree with the given `root`
+pub fn verify_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed_root = proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling));
+
+    computed_root.eq(root)
+}
+
+/// A single row of the allowlist
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllowlistEntry {
+    pub user: Pubkey,
+    pub allocation: u64,
+}
+
+/// A row of the allowlist CSV that can't be used, `line` counting from 1
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("line {line}: {reason}")]
+pub struct AllowlistCsvError {
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+/// Off-chain Merkle tree builder. Uses the exact same hashing as `verify_proof`,
+/// so the root and proofs it produces can be passed straight to the program.
+pub struct MerkleTree {
+    // layers[0] are the leaves, the last layer holds only the root
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(entries: &[AllowlistEntry]) -> Self {
+        let leaves: Vec<[u8; 32]> = entries
+            .iter()
+            .map(|entry| leaf_hash(&entry.user, entry.allocation))
+            .collect();
+
+        let mut layers = vec![leaves];
+
+        while layers[layers.len() - 1].len() > 1 {
+            let next_layer = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    // Odd node out gets promoted to the next layer as is
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+
+            layers.push(next_layer);
+        }
+
+        Self { layers }
+    }
+
+    /// Parses a `wallet,allocation` CSV. The allocation column is optional, and so is a
+    /// `wallet[,allocation]` header on the first row. Each wallet may only appear once.
+    pub fn parse_csv(csv: &str) -> Result<Vec<AllowlistEntry>, AllowlistCsvError> {
+        let mut entries = vec![];
+        let mut users = HashSet::new();
+        let mut is_first_row = true;
+
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |reason| AllowlistCsvError {
+                line: index + 1,
+                reason,
+            };
+            let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+
+            if is_first_row {
+                is_first_row = false;
+
+                let is_header = match columns.as_slice() {
+                    [wallet] => wallet.eq_ignore_ascii_case("wallet"),
+                    [wallet, allocation] => {
+                        wallet.eq_ignore_ascii_case("wallet")
+                            && allocation.eq_ignore_ascii_case("allocation")
+                    }
+                    _ => false,
+                };
+                if is_header {
+                    continue;
+                }
+            }
+
+            let (wallet, allocation) = match columns.as_slice() {
+                [wallet] => (wallet, None),
+                [wallet, allocation] => (wallet, Some(allocation)),
+                _ => return Err(error("expected a wallet and an optional allocation")),
+            };
+
+            let user = Pubkey::from_str(wallet).map_err(|_| error("invalid wallet"))?;
+
+            let allocation = match allocation {
+                Some(allocation) if !allocation.is_empty() => allocation
+                    .parse::<u64>()
+                    .map_err(|_| error("invalid allocation"))?,
+                _ => 0u64,
+            };
+
+            if !users.insert(user) {
+                return Err(error("duplicate wallet"));
+            }
+
+            entries.push(AllowlistEntry { user, allocation });
+        }
+
+        Ok(entries)
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the proof for the leaf at `index`, from the bottom of the tree upwards
+    pub fn proof(&self, mut index: usize) -> Option<Vec<[u8; 32]>> {
+        if index >= self.layers[0].len() {
+            return None;
+        }
+
+        let mut proof = vec![];
+
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling_index = index ^ 1;
+            if let Some(sibling) = layer.get(sibling_index) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(count: usize) -> Vec<AllowlistEntry> {
+        (0..count)
+            .map(|index| AllowlistEntry {
+                user: Pubkey::new_unique(),
+                allocation: index as u64 * 100,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prove_every_leaf_of_odd_sized_trees() {
+        for count in [1, 3, 5, 7] {
+            let entries = entries(count);
+            let tree = MerkleTree::new(&entries);
+
+            for (index, entry) in entries.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert!(verify_proof(
+                    &proof,
+                    &tree.root(),
+                    leaf_hash(&entry.user, entry.allocation)
+                ));
+            }
+
+            assert_eq!(tree.proof(count), None);
+        }
+    }
+
+    #[test]
+    fn reject_tampered_proofs() {
+        let entries = entries(5);
+        let tree = MerkleTree::new(&entries);
+        let entry = &entries[2];
+        let proof = tree.proof(2).unwrap();
+
+        // Another allocation or user
+        assert!(!verify_proof(
+            &proof,
+            &tree.root(),
+            leaf_hash(&entry.user, entry.allocation + 1)
+        ));
+        assert!(!verify_proof(
+            &proof,
+            &tree.root(),
+            leaf_hash(&entries[3].user, entry.allocation)
+        ));
+
+        // A modified or truncated proof
+        let mut tampered_proof = proof.clone();
+        tampered_proof[0][0] ^= 1;
+        assert!(!verify_proof(
+            &tampered_proof,
+            &tree.root(),
+            leaf_hash(&entry.user, entry.allocation)
+        ));
+        assert!(!verify_proof(
+            &proof[1..],
+            &tree.root(),
+            leaf_hash(&entry.user, entry.allocation)
+        ));
+    }
+
+    #[test]
+    fn parse_allowlist_csv() {
+        let user = Pubkey::new_unique();
+        let other_user = Pubkey::new_unique();
+        let csv = format!("wallet,allocation\n{},250\n\n {} \n", user, other_user);
+
+        assert_eq!(
+            MerkleTree::parse_csv(&csv).unwrap(),
+            vec![
+                AllowlistEntry {
+                    user,
+                    allocation: 250,
+                },
+                AllowlistEntry {
+                    user: other_user,
+                    allocation: 0,
+                },
+            ]
+        );
+
+        let error = |line, reason| Err(AllowlistCsvError { line, reason });
+        let invalid_wallet = format!("{},1\nnot-a-wallet,2", user);
+        assert_eq!(
+            MerkleTree::parse_csv(&invalid_wallet),
+            error(2, "invalid wallet")
+        );
+        // Only a real header is skipped, not a mistyped first wallet
+        let invalid_first_wallet = format!("not-a-wallet,1\n{},2", user);
+        assert_eq!(
+            MerkleTree::parse_csv(&invalid_first_wallet),
+            error(1, "invalid wallet")
+        );
+        let invalid_allocation = format!("{},-1", user);
+        assert_eq!(
+            MerkleTree::parse_csv(&invalid_allocation),
+            error(1, "invalid allocation")
+        );
+        let overflowing_allocation = format!("{},18446744073709551616", user);
+        assert_eq!(
+            MerkleTree::parse_csv(&overflowing_allocation),
+            error(1, "invalid allocation")
+        );
+        let extra_column = format!("wallet\n{},1,2", user);
+        assert_eq!(
+            MerkleTree::parse_csv(&extra_column),
+            error(2, "expected a wallet and an optional allocation")
+        );
+        let duplicate_wallet = format!("{},1\n\n{},2", user, user);
+        assert_eq!(
+            MerkleTree::parse_csv(&duplicate_wallet),
+            error(3, "duplicate wallet")
+        );
+    }
+}
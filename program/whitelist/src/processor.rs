@@ -1,8 +1,9 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    clock::Clock,
+    clock::{Clock, UnixTimestamp},
     entrypoint::ProgramResult,
+    instruction::Instruction,
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
@@ -14,37 +15,63 @@ use solana_program::{
     sysvar::Sysvar,
 };
 use spl_associated_token_account::{
-    create_associated_token_account, get_associated_token_address,
-    id as associated_token_program_id,
+    get_associated_token_address, id as associated_token_program_id,
+    instruction::{create_associated_token_account, create_associated_token_account_idempotent},
 };
 use spl_token::{
     id as token_program_id,
-    instruction::{close_account, sync_native},
+    instruction::{close_account, initialize_account, sync_native, transfer, MAX_SIGNERS},
     native_mint::id as native_mint_account,
-    state::Account as TokenState,
+    state::{Account as TokenState, Multisig},
 };
 use spl_token_swap::{
+    curve::{base::CurveType, constant_price::ConstantPriceCurve},
     id as token_swap_program_id,
-    instruction::{swap, Swap},
+    instruction::{
+        swap, withdraw_single_token_type_exact_amount_out, Swap,
+        WithdrawSingleTokenTypeExactAmountOut,
+    },
     state::SwapVersion,
 };
 
-use std::convert::TryInto;
-
+use crate::event::WhiteListEvent;
 use crate::instruction::WhiteListInstruction;
+use crate::merkle::{leaf_hash, verify_proof};
 use crate::state::WhitelistPDAGlobalState;
 use crate::{
     error::WhiteListError::{
-        AccountAlreadyRedeemed, AccountNotWhitelisted, IncorrectPoolOwner, IncorrectStateAccount,
-        IncorrectTokenOwner,
+        AccountAlreadyRedeemed, AccountNotWhitelisted, AllocationExceeded, AmountNotPriceMultiple,
+        AssociatedAccountMismatch, ClaimsOutstanding, ContributionTooSmall, HardCapExceeded,
+        IncorrectAssociatedTokenProgram, IncorrectEntryAccount, IncorrectMint, IncorrectPdaAccount,
+        IncorrectPoolOwner, IncorrectStateAccount, IncorrectSwapProgram, IncorrectSystemProgram,
+        IncorrectTokenOwner, IncorrectTokenProgram, IncorrectUserTokenAccount,
+        IncorrectWhitelistAdmin, InsufficientFunds, InsufficientSol, InvalidSaleCaps,
+        InvalidSaleConfig, InvalidSaleSchedule, InvalidStateVersion, InvalidVestingSchedule,
+        InvalidWithdrawAmount, MathOverflow, MintMismatch, NotATokenAccount, NothingToClaim,
+        PoolMismatch, PoolNotInitialized, PriceMismatch, ProceedsOutstanding, RegistryFull,
+        SaleEnded, SaleModeMismatch, SaleNotEnded, SaleNotStarted, SalePaused, SaleTermsLocked,
+        SoftCapNotReached, SoftCapReached, TreasuryMismatch, VaultDepleted, VaultMismatch,
+        VaultNotEmpty,
     },
-    state::WhitelistUserState,
+    state::{
+        get_state_packed_len, SalePhase, WhitelistCreatorRegistry, WhitelistEntryState,
+        WhitelistUserState, GLOBAL_STATE_V1_LEN, GLOBAL_STATE_VERSION, MAX_BASIS_POINTS,
+        MAX_REGISTRY_SALES, MAX_SALE_PHASES,
+    },
+};
+use crate::{
+    find_whitelist_entry_program_address, find_whitelist_program_address,
+    find_whitelist_registry_program_address, find_whitelist_user_state_program_address,
+    find_whitelist_vesting_vault_program_address, WHITELIST_ENTRY_SEED, WHITELIST_PDA_SEED,
+    WHITELIST_REGISTRY_SEED, WHITELIST_USER_SEED, WHITELIST_VESTING_SEED,
 };
 
 // UTIL FUNCTIONS
+#[allow(clippy::too_many_arguments)]
 fn check_for_wrapping(
     instruction: &str,
     amount_to_be_wrapped: u64,
+    rent_storage_fees: u64,
     associated_account_address: &Pubkey,
     funding_account: &AccountInfo,
     associated_token_pda_account: &AccountInfo,
@@ -59,17 +86,16 @@ fn check_for_wrapping(
 
     // Checking if the funding account has enough lamports
     let funding_account_balance = funding_account.try_lamports()?;
-    let rent_storage_fees = match instruction {
-        "CreateAndWrapSOLToken" => Rent::get()?.minimum_balance(165),
-        "WrapSOLToken" => 0u64,
-        _ => 0u64,
-    };
-
-    let required_balance = amount_to_be_wrapped + rent_storage_fees;
+    let required_balance = amount_to_be_wrapped
+        .checked_add(rent_storage_fees)
+        .ok_or(MathOverflow)?;
 
     if funding_account_balance.lt(&required_balance) {
-        msg!("WhiteList CreateAndWrapSOLToken: Funding account has insufficient funds");
-        return Err(ProgramError::InsufficientFunds);
+        msg!(
+            "WhiteList {}: Funding account has insufficient funds",
+            instruction
+        );
+        return Err(InsufficientSol.into());
     }
 
     // Checking if the right PDA account has been passed
@@ -78,723 +104,4217 @@ fn check_for_wrapping(
         .eq(&associated_account_address)
     {
         msg!(
-            "WhiteList CreateAndWrapSOLToken: Associated Token Account mismatch with given address"
+            "WhiteList {}: Associated Token Account mismatch with given address",
+            instruction
         );
-        return Err(ProgramError::InvalidAccountData);
+        return Err(AssociatedAccountMismatch.into());
     }
 
     // Checking if the right spl token program account has been passed
     if !spl_token_program_account.key.eq(&token_program_id()) {
-        msg!("WhiteList CreateAndWrapSOLToken: Passed Token Program key mismatches with original Token Program key");
-        return Err(ProgramError::IncorrectProgramId);
+        msg!(
+            "WhiteList {}: Passed Token Program key mismatches with original Token Program key",
+            instruction
+        );
+        return Err(IncorrectTokenProgram.into());
     }
 
     // Checking if the right system program account has been passed
     if !system_program_account.key.eq(&system_program_id()) {
-        msg!("WhiteList CreateAndWrapSOLToken: Passed System Program key mismatches with original System Program key");
-        return Err(ProgramError::IncorrectProgramId);
+        msg!(
+            "WhiteList {}: Passed System Program key mismatches with original System Program key",
+            instruction
+        );
+        return Err(IncorrectSystemProgram.into());
     }
 
     Ok(())
 }
 
-pub struct WhiteListProcessor;
+fn check_pool_binding(
+    instruction: &str,
+    token_swap_pool_state_account: &AccountInfo,
+    y_token_mint_account: &AccountInfo,
+    y_token_account: &AccountInfo,
+    quote_token_account: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    // Checking if the pool state account passed is the correct account
+    if !token_swap_pool_state_account
+        .owner
+        .eq(&token_swap_program_id())
+    {
+        msg!(
+            "Whitelist {}: Passed Token Swap State Account's owner is not the Token Swap Program",
+            instruction
+        );
+        return Err(IncorrectPoolOwner.into());
+    }
 
-impl WhiteListProcessor {
-    // INITIALIZE
-    fn process_whitelist_initialize(
-        authorized_addresses: Vec<Pubkey>,
-        whitelist_pda_bump: u8,
-        price_per_token_y: u64,
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-    ) -> ProgramResult {
-        msg!("DONE PDA BUMP PARSE :) {}", whitelist_pda_bump.to_string());
+    // Checking if the pool state account is initialized
+    let pool_state_account_decoded =
+        SwapVersion::unpack(&token_swap_pool_state_account.data.borrow())?;
+
+    if !pool_state_account_decoded.is_initialized() {
         msg!(
-            "DONE PRICE PER TOKEN Y PARSE :) {}",
-            price_per_token_y.to_string()
+            "Whitelist {}: Token Swap State Account not intialized",
+            instruction
         );
+        return Err(PoolNotInitialized.into());
+    }
+
+    // Checking if the token accounts's owner is the token program
+    if !y_token_account.owner.eq(&token_program_id()) {
         msg!(
-            "DONE AUTHORIZED ADDRESS 1 PARSE :) {}",
-            authorized_addresses[0].to_string()
+            "Whitelist {}: Y Token account passed is not a Token Program Account",
+            instruction
         );
+        return Err(NotATokenAccount.into());
+    }
+
+    if !quote_token_account.owner.eq(&token_program_id()) {
         msg!(
-            "DONE AUTHORIZED ADDRESS 2 PARSE :) {}",
-            authorized_addresses[1].to_string()
+            "Whitelist {}: Quote Token account passed is not a Token Program Account",
+            instruction
         );
+        return Err(NotATokenAccount.into());
+    }
+
+    // Checking if the token accounts owner is the swap authority
+    let swap_authority = Pubkey::create_program_address(
+        &[
+            &token_swap_pool_state_account.key.to_bytes(),
+            &[pool_state_account_decoded.bump_seed()],
+        ],
+        &token_swap_program_id(),
+    )?;
+
+    let y_token_account_decoded = TokenState::unpack(&y_token_account.data.borrow())?;
+    let quote_token_account_decoded = TokenState::unpack(&quote_token_account.data.borrow())?;
+
+    if !y_token_account_decoded.owner.eq(&swap_authority) {
         msg!(
-            "DONE AUTHORIZED ADDRESS 3 PARSE :) {}",
-            authorized_addresses[2].to_string()
+            "Whitelist {}: Y Token Account's owner is not the Swap Authority",
+            instruction
         );
+        return Err(IncorrectTokenOwner.into());
+    }
+
+    if !quote_token_account_decoded.owner.eq(&swap_authority) {
         msg!(
-            "DONE AUTHORIZED ADDRESS 4 PARSE :) {}",
-            authorized_addresses[3].to_string()
+            "Whitelist {}: Quote Token Account's owner is not the Swap Authority",
+            instruction
         );
+        return Err(IncorrectTokenOwner.into());
+    }
+
+    // Checking if the Y token account holds the Y token mint passed
+    if !y_token_account_decoded.mint.eq(y_token_mint_account.key) {
         msg!(
-            "DONE AUTHORIZED ADDRESS 5 PARSE :) {}",
-            authorized_addresses[4].to_string()
+            "Whitelist {}: Y Token Account's mint is not the Y Token Mint",
+            instruction
         );
+        return Err(IncorrectMint.into());
+    }
+
+    // Checking if both token accounts are the two sides of the pool
+    let pool_token_accounts = [
+        *pool_state_account_decoded.token_a_account(),
+        *pool_state_account_decoded.token_b_account(),
+    ];
+
+    if !pool_token_accounts.contains(y_token_account.key)
+        || !pool_token_accounts.contains(quote_token_account.key)
+        || y_token_account.key.eq(quote_token_account.key)
+    {
         msg!(
-            "DONE AUTHORIZED ADDRESS 6 PARSE :) {}",
-            authorized_addresses[5].to_string()
+            "Whitelist {}: Y and Quote Token Accounts are not the two sides of the pool",
+            instruction
         );
+        return Err(PoolMismatch.into());
+    }
 
-        // All accounts
-        let accounts_iterable = &mut accounts.iter();
-        let whitelist_creator = next_account_info(accounts_iterable)?;
-        let whitelist_pda_account = next_account_info(accounts_iterable)?;
-        let token_swap_pool_state_account = next_account_info(accounts_iterable)?;
-        let y_token_mint_account = next_account_info(accounts_iterable)?;
-        let y_token_account = next_account_info(accounts_iterable)?;
-        let native_sol_token_account = next_account_info(accounts_iterable)?;
-        let system_program_account = next_account_info(accounts_iterable)?;
+    // The quote mint is whatever the pool pairs the Y token with
+    Ok(quote_token_account_decoded.mint)
+}
 
-        let rent = Rent::get()?;
+fn check_fixed_price_pool(
+    instruction: &str,
+    token_swap_pool_state_account: &AccountInfo,
+    y_token_account: &AccountInfo,
+    price_per_token_y: u64,
+) -> ProgramResult {
+    let pool_state_account_decoded =
+        SwapVersion::unpack(&token_swap_pool_state_account.data.borrow())?;
+    let swap_curve = pool_state_account_decoded.swap_curve();
 
-        let pda_seeds_bump: &[&[u8]] = &[
-            b"whitelistpda",
-            &whitelist_creator.key.to_bytes(),
-            &y_token_account.key.to_bytes(),
-            &[whitelist_pda_bump],
-        ];
+    // The constant price curve prices token B in token A, so Y has to be the B side
+    if swap_curve.curve_type != CurveType::ConstantPrice
+        || !pool_state_account_decoded
+            .token_b_account()
+            .eq(y_token_account.key)
+    {
+        msg!(
+            "Whitelist {}: Pool does not sell Y at a constant price",
+            instruction
+        );
+        return Err(PriceMismatch.into());
+    }
 
-        let whitelist_program_address = Pubkey::create_program_address(pda_seeds_bump, program_id)?;
+    let mut packed_curve = [0u8; ConstantPriceCurve::LEN];
+    swap_curve.calculator.pack_into_slice(&mut packed_curve);
+    let curve = ConstantPriceCurve::unpack_from_slice(&packed_curve)?;
 
-        const PDA_ACCOUNT_SPAN: u64 = 362;
-        let lamports_required = rent.minimum_balance(PDA_ACCOUNT_SPAN.try_into().unwrap());
+    if curve.token_b_price != price_per_token_y {
+        msg!(
+            "Whitelist {}: Pool price of Y does not match the price per token Y",
+            instruction
+        );
+        return Err(PriceMismatch.into());
+    }
 
-        // Checking if the pool state account passed is the correct account
-        if !token_swap_pool_state_account
-            .owner
-            .eq(&token_swap_program_id())
-        {
-            msg!("Whitelist Initialize: Passed Token Swap State Account's owner is not the Token Swap Program");
-            return Err(ProgramError::InvalidAccountData);
-        }
+    Ok(())
+}
 
-        // Checking if the pool state account is initialized
-        let pool_state_account_decoded =
-            SwapVersion::unpack(&token_swap_pool_state_account.data.borrow())?;
+fn validate_signer(
+    instruction: &str,
+    signer_account: &AccountInfo,
+    multisig_signers: &[AccountInfo],
+) -> ProgramResult {
+    // An SPL multisig signs through M of its N signers, passed as extra accounts
+    if signer_account.owner.eq(&token_program_id())
+        && signer_account.data_len() == Multisig::get_packed_len()
+    {
+        let multisig = Multisig::unpack(&signer_account.data.borrow())?;
+        let mut num_signers = 0u8;
+        let mut matched = [false; MAX_SIGNERS];
 
-        if !pool_state_account_decoded.is_initialized() {
-            msg!("Whitelist Initialize: Token Swap State Account not intialized");
-            return Err(IncorrectPoolOwner.into());
+        for signer in multisig_signers.iter() {
+            for (position, key) in multisig.signers[0..multisig.n as usize].iter().enumerate() {
+                if key.eq(signer.key) && !matched[position] {
+                    if !signer.is_signer {
+                        msg!("Whitelist {}: Multisig signer has not signed", instruction);
+                        return Err(ProgramError::MissingRequiredSignature);
+                    }
+                    matched[position] = true;
+                    num_signers += 1;
+                }
+            }
         }
 
-        // Checking if the token accounts's owner is the token program
-        if !y_token_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist Initialize: Y Token account passed is not a Token Program Account");
-            return Err(ProgramError::InvalidAccountData);
+        if num_signers < multisig.m {
+            msg!(
+                "Whitelist {}: Only {} of {} multisig signers have signed",
+                instruction,
+                num_signers,
+                multisig.m
+            );
+            return Err(ProgramError::MissingRequiredSignature);
         }
 
-        if !native_sol_token_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist Initialize: Y Token account passed is not a Token Program Account");
-            return Err(ProgramError::InvalidAccountData);
-        }
+        return Ok(());
+    }
 
-        // Checking if the token accounts owner is the swap authority
-        let swap_authority = Pubkey::create_program_address(
-            &[
-                &token_swap_pool_state_account.key.to_bytes(),
-                &[pool_state_account_decoded.bump_seed()],
-            ],
-            &token_swap_program_id(),
-        )?;
+    if !signer_account.is_signer {
+        msg!(
+            "Whitelist {}: Authority account has not signed",
+            instruction
+        );
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-        let y_token_account_decoded = TokenState::unpack(&y_token_account.data.borrow())?;
-        let native_token_account_decoded =
-            TokenState::unpack(&native_sol_token_account.data.borrow())?;
+    Ok(())
+}
 
-        if !y_token_account_decoded.owner.eq(&swap_authority) {
-            msg!("Whitelist Initialize: Y Token Account's owner is not the Swap Authority");
-            return Err(IncorrectTokenOwner.into());
-        }
+fn check_whitelist_authority(
+    instruction: &str,
+    authority_account: &AccountInfo,
+    whitelist_global_state: &WhitelistPDAGlobalState,
+    multisig_signers: &[AccountInfo],
+) -> ProgramResult {
+    // Checking if the signer is the whitelist authority
+    if !authority_account
+        .key
+        .eq(&whitelist_global_state.whitelist_authority)
+    {
+        msg!(
+            "Whitelist {}: Signer is not the whitelist authority",
+            instruction
+        );
+        return Err(IncorrectWhitelistAdmin.into());
+    }
 
-        if !native_token_account_decoded.owner.eq(&swap_authority) {
-            msg!(
-                "Whitelist Initialize: Native SOL Token Account's owner is not the Swap Authority"
-            );
-            return Err(IncorrectTokenOwner.into());
-        }
+    validate_signer(instruction, authority_account, multisig_signers)
+}
 
-        // Checking about the value of price per token B
-        if price_per_token_y == 0 {
-            msg!("Whitelist Initialize: Price per Token B should be greater than 0");
-            return Err(ProgramError::InvalidInstructionData);
-        }
+fn check_whitelist_delegate(
+    instruction: &str,
+    delegate_account: &AccountInfo,
+    whitelist_global_state: &WhitelistPDAGlobalState,
+    delegate: &Pubkey,
+    multisig_signers: &[AccountInfo],
+) -> ProgramResult {
+    // Checking if the signer is either the authority or the delegated key
+    if !delegate_account
+        .key
+        .eq(&whitelist_global_state.whitelist_authority)
+        && !delegate_account.key.eq(delegate)
+    {
+        msg!(
+            "Whitelist {}: Signer is neither the whitelist authority nor its delegate",
+            instruction
+        );
+        return Err(IncorrectWhitelistAdmin.into());
+    }
 
-        // Checking if the resultant PDA address matches
-        if !whitelist_pda_account.key.eq(&whitelist_program_address) {
-            msg!("Whitelist Initialize: PDA accounts mismatch");
-            return Err(ProgramError::InvalidAccountData);
-        }
+    validate_signer(instruction, delegate_account, multisig_signers)
+}
 
-        // Checking if the pool owner has signed
-        if !whitelist_creator.is_signer {
-            msg!("Whitelist Initialize: Pool creator has not signed");
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+// Closes a program owned account by draining its lamports to the destination and handing it
+// back to the system program without any data, so it can't be revived within the transaction
+fn close_program_account(
+    account: &AccountInfo,
+    destination_account: &AccountInfo,
+) -> ProgramResult {
+    let account_lamports = account.lamports();
+    **destination_account.try_borrow_mut_lamports()? = destination_account
+        .lamports()
+        .checked_add(account_lamports)
+        .ok_or(MathOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
 
-        // Checking if the system program id is the one expected
-        if !system_program_account.key.eq(&system_program_id()) {
-            msg!("Whitelist Initialize: System Program accounts mismatch");
-            return Err(ProgramError::InvalidAccountData);
-        }
+    account.realloc(0, false)?;
+    account.assign(&system_program_id());
+
+    Ok(())
+}
+
+fn check_sale_not_paused(
+    instruction: &str,
+    whitelist_global_state: &WhitelistPDAGlobalState,
+) -> ProgramResult {
+    if whitelist_global_state.is_paused {
+        msg!("Whitelist {}: Sale is paused", instruction);
+        return Err(SalePaused.into());
+    }
+
+    Ok(())
+}
 
-        // CPI Part 1 -> Allocating space for PDA
+fn unpack_whitelist_global_state(
+    instruction: &str,
+    whitelist_global_state_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<WhitelistPDAGlobalState, ProgramError> {
+    // Checking if the global state account is part of this program
+    if !whitelist_global_state_account.owner.eq(program_id) {
         msg!(
-            "Allocating space for PDA account {}",
-            whitelist_pda_account.key.to_string()
+            "Whitelist {}: Passed Whitelist global state account is invalid",
+            instruction
         );
-        let allocate_space_pda_ix =
-            system_instruction::allocate(&whitelist_pda_account.key, PDA_ACCOUNT_SPAN);
-
-        invoke_signed(
-            &allocate_space_pda_ix,
-            &[
-                whitelist_pda_account.clone(),
-                system_program_account.clone(),
-            ],
-            &[pda_seeds_bump],
-        )?;
+        return Err(IncorrectStateAccount.into());
+    }
 
-        // CPI Part 2 -> Assigning the owner for the PDA
-        msg!("Assigning the program owner for the newly created PDA");
-        let assign_owner_to_pda_ix =
-            system_instruction::assign(&whitelist_pda_account.key, &program_id);
+    // Checking if the global state account is at the current layout version
+    if whitelist_global_state_account.data.borrow().first() != Some(&GLOBAL_STATE_VERSION) {
+        msg!(
+            "Whitelist {}: Global state layout is outdated, migrate it first",
+            instruction
+        );
+        return Err(InvalidStateVersion.into());
+    }
 
-        invoke_signed(
-            &assign_owner_to_pda_ix,
-            &[
-                whitelist_pda_account.clone(),
-                system_program_account.clone(),
-            ],
-            &[pda_seeds_bump],
-        )?;
+    let whitelist_global_state =
+        WhitelistPDAGlobalState::unpack(&whitelist_global_state_account.data.borrow())?;
 
-        // CPI Part 3 -> Transfer Lamports to the newly created PDA
-        msg!("Transferring lamports to PDA account to make rent exempt");
-        let transfer_lamports_to_pda_ix = system_instruction::transfer(
-            &whitelist_creator.key,
-            &whitelist_pda_account.key,
-            lamports_required,
+    // Checking if the global state account has been initialized
+    if !whitelist_global_state.is_initialized() {
+        msg!(
+            "Whitelist {}: Global state not initialized yet",
+            instruction
         );
+        return Err(ProgramError::UninitializedAccount);
+    }
 
-        invoke(
-            &transfer_lamports_to_pda_ix,
+    check_whitelist_global_address(
+        instruction,
+        whitelist_global_state_account,
+        &whitelist_global_state,
+        program_id,
+    )?;
+
+    Ok(whitelist_global_state)
+}
+
+fn check_whitelist_global_address(
+    instruction: &str,
+    whitelist_global_state_account: &AccountInfo,
+    whitelist_global_state: &WhitelistPDAGlobalState,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let is_expected_address = if whitelist_global_state.has_legacy_seeds {
+        // Checking if a migrated global state is still at the address of its version 1 seeds
+        Pubkey::create_program_address(
             &[
-                whitelist_creator.clone(),
-                whitelist_pda_account.clone(),
-                system_program_account.clone(),
+                WHITELIST_PDA_SEED,
+                whitelist_global_state.whitelist_creator.as_ref(),
+                whitelist_global_state.seed_y_token_account.as_ref(),
+                &[whitelist_global_state.global_pda_bump],
             ],
-        )?;
+            program_id,
+        ) == Ok(*whitelist_global_state_account.key)
+    } else {
+        // Checking if the global state is the canonical derivation of its sale's seeds
+        let (whitelist_global_address, whitelist_pda_bump) = find_whitelist_program_address(
+            &whitelist_global_state.whitelist_creator,
+            &whitelist_global_state.seed_y_token_account,
+            whitelist_global_state.sale_id,
+            program_id,
+        );
 
-        // Assigning basic state now
-        let mut whitelist_pda_state =
-            WhitelistPDAGlobalState::try_from_slice(&whitelist_pda_account.data.borrow())?;
+        whitelist_global_state_account
+            .key
+            .eq(&whitelist_global_address)
+            && whitelist_global_state.global_pda_bump == whitelist_pda_bump
+    };
 
-        // Checking if the PDA has already been initialized
-        if whitelist_pda_state.is_initialized {
-            msg!("Whitelist Initialize: PDA account has already been initialized");
+    if !is_expected_address {
+        msg!(
+            "Whitelist {}: Global state is not the canonical PDA of its sale",
+            instruction
+        );
+        return Err(IncorrectPdaAccount.into());
+    }
 
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
+    Ok(())
+}
 
+// Invokes `instruction` with the global state PDA signing, from the seeds of its sale
+fn invoke_signed_by_whitelist_pda(
+    instruction: &Instruction,
+    account_infos: &[AccountInfo],
+    whitelist_global_state: &WhitelistPDAGlobalState,
+) -> ProgramResult {
+    let sale_id_bytes = whitelist_global_state.sale_id.to_le_bytes();
+    let bump = [whitelist_global_state.global_pda_bump];
+    let pda_seeds_bump: &[&[u8]] = if whitelist_global_state.has_legacy_seeds {
+        &[
+            WHITELIST_PDA_SEED,
+            whitelist_global_state.whitelist_creator.as_ref(),
+            whitelist_global_state.seed_y_token_account.as_ref(),
+            &bump,
+        ]
+    } else {
+        &[
+            WHITELIST_PDA_SEED,
+            whitelist_global_state.whitelist_creator.as_ref(),
+            whitelist_global_state.seed_y_token_account.as_ref(),
+            &sale_id_bytes,
+            &bump,
+        ]
+    };
+
+    invoke_signed(instruction, account_infos, &[pda_seeds_bump])
+}
+
+fn unpack_whitelist_entry(
+    instruction: &str,
+    whitelist_entry_account: &AccountInfo,
+    whitelist_global_state_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<WhitelistEntryState, ProgramError> {
+    // Checking if the entry account is part of this program
+    if !whitelist_entry_account.owner.eq(program_id) {
         msg!(
-            "Initializing the PDA Account of address {}",
-            whitelist_program_address
+            "Whitelist {}: Passed Whitelist entry account is invalid",
+            instruction
         );
+        return Err(IncorrectStateAccount.into());
+    }
 
-        whitelist_pda_state.is_initialized = true;
-        whitelist_pda_state.whitelist_creator = *whitelist_creator.key;
-        whitelist_pda_state.global_pda_bump = whitelist_pda_bump;
-        whitelist_pda_state.whitelist_auth_addresses = [
-            authorized_addresses[0],
-            authorized_addresses[1],
-            authorized_addresses[2],
-            authorized_addresses[3],
-            authorized_addresses[4],
-            authorized_addresses[5],
-        ];
-        whitelist_pda_state.native_sol_token_account = *native_sol_token_account.key;
-        whitelist_pda_state.token_swap_pool_state = *token_swap_pool_state_account.key;
-        whitelist_pda_state.y_mint_account = *y_token_mint_account.key;
-        whitelist_pda_state.y_token_account = *y_token_account.key;
-        whitelist_pda_state.price_per_token_y = price_per_token_y;
+    let whitelist_entry_state =
+        WhitelistEntryState::try_from_slice(&whitelist_entry_account.data.borrow())?;
 
-        whitelist_pda_state.serialize(&mut &mut whitelist_pda_account.data.borrow_mut()[..])?;
+    if !whitelist_entry_state.is_initialized() {
+        msg!("Whitelist {}: Entry account not initialized", instruction);
+        return Err(ProgramError::UninitializedAccount);
+    }
 
-        msg!("Creation of Whitelist PDA complete successfully");
+    // Checking if the entry belongs to the passed global state
+    if !whitelist_entry_state
+        .whitelist_global_state
+        .eq(whitelist_global_state_account.key)
+    {
+        msg!(
+            "Whitelist {}: Entry account belongs to another whitelist",
+            instruction
+        );
+        return Err(IncorrectEntryAccount.into());
+    }
 
-        Ok(())
+    // Checking if the entry account is the PDA derived for its user
+    let whitelist_entry_address = Pubkey::create_program_address(
+        &[
+            WHITELIST_ENTRY_SEED,
+            &whitelist_global_state_account.key.to_bytes(),
+            &whitelist_entry_state.user.to_bytes(),
+            &[whitelist_entry_state.entry_bump],
+        ],
+        program_id,
+    )?;
+
+    if !whitelist_entry_account.key.eq(&whitelist_entry_address) {
+        msg!("Whitelist {}: Entry account PDA mismatch", instruction);
+        return Err(IncorrectEntryAccount.into());
     }
 
-    // PLAIN ASSOC WRAP SOL
-    fn process_whitelist_wrap_sol(
-        amount_to_be_wrapped: u64,
-        accounts: &[AccountInfo],
-    ) -> ProgramResult {
-        let accounts_iterable = &mut accounts.iter();
-        // All accounts
-        let funding_account = next_account_info(accounts_iterable)?;
-        let associated_token_pda_account = next_account_info(accounts_iterable)?;
-        let system_program_account = next_account_info(accounts_iterable)?;
-        let spl_token_program_account = next_account_info(accounts_iterable)?;
+    Ok(whitelist_entry_state)
+}
 
-        let associated_account_address =
-            get_associated_token_address(&funding_account.key, &native_mint_account());
+fn check_sale_schedule(
+    instruction: &str,
+    sale_start_ts: UnixTimestamp,
+    sale_end_ts: UnixTimestamp,
+    phases: &[SalePhase],
+) -> ProgramResult {
+    // Checking if the sale ends after it starts (0 means the sale never ends)
+    if sale_end_ts != 0 && sale_end_ts <= sale_start_ts {
+        msg!(
+            "Whitelist {}: Sale end is not after the sale start",
+            instruction
+        );
+        return Err(InvalidSaleSchedule.into());
+    }
 
-        // Processes a bunch of checks which are common for both create-wrap and wrap SOL
+    if phases.len() > MAX_SALE_PHASES {
+        msg!(
+            "Whitelist {}: At most {} sale phases are supported",
+            instruction,
+            MAX_SALE_PHASES
+        );
+        return Err(InvalidSaleSchedule.into());
+    }
 
-        check_for_wrapping(
-            "WrapSOLToken",
-            amount_to_be_wrapped,
-            &associated_account_address,
-            funding_account,
-            associated_token_pda_account,
-            spl_token_program_account,
-            system_program_account,
-        )?;
+    // Checking if the phases are ordered and fall inside the sale window
+    let mut previous_phase_start_ts = sale_start_ts;
 
-        // Checking if the associated account already exists
-        if !associated_token_pda_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist WrapSOLToken: Associated token account does not exist");
-            return Err(ProgramError::UninitializedAccount);
+    for (index, phase) in phases.iter().enumerate() {
+        let is_ordered = if index == 0 {
+            phase.start_ts >= previous_phase_start_ts
+        } else {
+            phase.start_ts > previous_phase_start_ts
+        };
+
+        if !is_ordered || (sale_end_ts != 0 && phase.start_ts >= sale_end_ts) {
+            msg!(
+                "Whitelist {}: Sale phase {} is out of order or outside the sale window",
+                instruction,
+                index
+            );
+            return Err(InvalidSaleSchedule.into());
         }
 
-        // Business logic starts
+        previous_phase_start_ts = phase.start_ts;
+    }
 
-        // Transferring the required lamports to be wrapped to the PDA
-        msg!("Transferring SOL to Associated account");
-        let transfer_lamports_to_assoc_account_ix = system_instruction::transfer(
-            &funding_account.key,
-            &associated_account_address,
+    Ok(())
+}
+
+// Unpacks a user state, checking it is the user's PDA for the given whitelist
+fn unpack_whitelist_user_state(
+    instruction: &str,
+    user_account: &AccountInfo,
+    whitelist_user_state_account: &AccountInfo,
+    whitelist_global_state_account: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<WhitelistUserState, ProgramError> {
+    if !whitelist_user_state_account.owner.eq(program_id) {
+        msg!(
+            "Whitelist {}: Passed Whitelist user state account is invalid",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    let whitelist_user_state =
+        WhitelistUserState::try_from_slice(&whitelist_user_state_account.data.borrow())?;
+
+    // Checking if the user state belongs to the user
+    if !whitelist_user_state.is_initialized()
+        || !whitelist_user_state
+            .whitelisted_by_account
+            .eq(user_account.key)
+    {
+        msg!(
+            "Whitelist {}: User State belongs to another user",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    // Checking if the user state is the PDA derived from the passed global state
+    let whitelist_user_state_address = Pubkey::create_program_address(
+        &[
+            WHITELIST_USER_SEED,
+            &whitelist_global_state_account.key.to_bytes(),
+            &user_account.key.to_bytes(),
+            &[whitelist_user_state.user_state_bump],
+        ],
+        program_id,
+    )?;
+
+    if !whitelist_user_state_account
+        .key
+        .eq(&whitelist_user_state_address)
+    {
+        msg!("Whitelist {}: User state PDA account mismatch", instruction);
+        return Err(IncorrectStateAccount.into());
+    }
+
+    Ok(whitelist_user_state)
+}
+
+// Derives the user state PDA and creates it on the user's first purchase
+fn load_whitelist_user_state<'a>(
+    instruction: &str,
+    user_account: &AccountInfo<'a>,
+    whitelist_user_state_account: &AccountInfo<'a>,
+    whitelist_global_state_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    program_id: &Pubkey,
+) -> Result<(WhitelistUserState, u8), ProgramError> {
+    // Checking if the user state account is the PDA derived for this user
+    let (whitelist_user_state_address, user_state_bump) = find_whitelist_user_state_program_address(
+        whitelist_global_state_account.key,
+        user_account.key,
+        program_id,
+    );
+
+    if !whitelist_user_state_account
+        .key
+        .eq(&whitelist_user_state_address)
+    {
+        msg!("Whitelist {}: User state PDA account mismatch", instruction);
+        return Err(IncorrectStateAccount.into());
+    }
+
+    // Creating the user state PDA on the user's first purchase
+    if whitelist_user_state_account.owner.eq(&system_program_id()) {
+        // Checking if the system program id is the one expected
+        if !system_program_account.key.eq(&system_program_id()) {
+            msg!(
+                "Whitelist {}: System Program accounts mismatch",
+                instruction
+            );
+            return Err(IncorrectSystemProgram.into());
+        }
+
+        let user_state_seeds_bump: &[&[u8]] = &[
+            WHITELIST_USER_SEED,
+            &whitelist_global_state_account.key.to_bytes(),
+            &user_account.key.to_bytes(),
+            &[user_state_bump],
+        ];
+
+        create_pda_account(
+            user_account,
+            whitelist_user_state_account,
+            system_program_account,
+            user_state_seeds_bump,
+            get_state_packed_len::<WhitelistUserState>(),
+            program_id,
+        )?;
+    } else if !whitelist_user_state_account.owner.eq(program_id) {
+        msg!(
+            "Whitelist {}: Passed Whitelist user state aaccount is invalid",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    let whitelist_user_state =
+        WhitelistUserState::try_from_slice(&whitelist_user_state_account.data.borrow())?;
+
+    // Checking if an already initialized User state belongs to this user
+    if whitelist_user_state.is_initialized()
+        && !whitelist_user_state
+            .whitelisted_by_account
+            .eq(user_account.key)
+    {
+        msg!(
+            "Whitelist {}: User State belongs to another user",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    Ok((whitelist_user_state, user_state_bump))
+}
+
+// Checks a purchase against the sale window, phases, membership and contribution caps,
+// returning the price per Y token and the user's new total contribution
+#[allow(clippy::too_many_arguments)]
+fn check_purchase(
+    instruction: &str,
+    input_sol_amount: u64,
+    allocation: u64,
+    proof: &[[u8; 32]],
+    current_network_time: UnixTimestamp,
+    user_account: &AccountInfo,
+    whitelist_global_state_account: &AccountInfo,
+    whitelist_global_state: &WhitelistPDAGlobalState,
+    whitelist_user_state: &WhitelistUserState,
+    whitelist_entry_account: Option<&AccountInfo>,
+    program_id: &Pubkey,
+) -> Result<(u64, u64), ProgramError> {
+    check_sale_not_paused(instruction, whitelist_global_state)?;
+
+    // Checking if the sale is currently open
+    if current_network_time < whitelist_global_state.sale_start_ts {
+        msg!("Whitelist {}: Sale has not started yet", instruction);
+        return Err(SaleNotStarted.into());
+    }
+
+    if whitelist_global_state.sale_end_ts != 0
+        && current_network_time >= whitelist_global_state.sale_end_ts
+    {
+        msg!("Whitelist {}: Sale has already ended", instruction);
+        return Err(SaleEnded.into());
+    }
+
+    let current_phase = whitelist_global_state.current_phase(current_network_time);
+
+    // Phase price and cap take precedence over the global ones when set
+    let price_per_token_y = match current_phase {
+        Some(phase) if phase.price_per_token_y > 0 => phase.price_per_token_y,
+        _ => whitelist_global_state.price_per_token_y,
+    };
+
+    let default_max_contribution = match current_phase {
+        Some(phase) if phase.max_contribution > 0 => phase.max_contribution,
+        _ => whitelist_global_state.max_contribution,
+    };
+
+    let is_public_phase = matches!(current_phase, Some(phase) if phase.is_public);
+
+    // Checking if the user account is whitelisted, either by an entry account or a Merkle proof
+    let allocation = match whitelist_entry_account {
+        // Anyone can buy during a public phase
+        _ if is_public_phase => 0u64,
+        Some(whitelist_entry_account) => {
+            let whitelist_entry_state = unpack_whitelist_entry(
+                instruction,
+                whitelist_entry_account,
+                whitelist_global_state_account,
+                program_id,
+            )?;
+
+            if !whitelist_entry_state.user.eq(user_account.key) {
+                msg!(
+                    "Whitelist {}: Passed user account is not allowed to buy",
+                    instruction
+                );
+                return Err(AccountNotWhitelisted.into());
+            }
+
+            whitelist_entry_state.allocation
+        }
+        None => {
+            let is_allowed = verify_proof(
+                proof,
+                &whitelist_global_state.whitelist_merkle_root,
+                leaf_hash(user_account.key, allocation),
+            );
+
+            if !is_allowed {
+                msg!(
+                    "Whitelist {}: Passed user account is not allowed to buy",
+                    instruction
+                );
+                return Err(AccountNotWhitelisted.into());
+            }
+
+            allocation
+        }
+    };
+
+    // Sales migrated from version 1 keep its single purchase per user
+    if whitelist_global_state.has_legacy_seeds && whitelist_user_state.total_contributed > 0 {
+        msg!(
+            "Whitelist {}: User already redeemed the full allocation",
+            instruction
+        );
+        return Err(AccountAlreadyRedeemed.into());
+    }
+
+    // The user's allocation overrides the default maximum contribution (0 means uncapped)
+    let max_contribution = match allocation {
+        0 => default_max_contribution,
+        allocation => allocation,
+    };
+
+    let total_contributed = whitelist_user_state
+        .total_contributed
+        .checked_add(input_sol_amount)
+        .ok_or(MathOverflow)?;
+
+    if max_contribution > 0 && total_contributed.gt(&max_contribution) {
+        // Checking if the user has nothing left to contribute at all
+        if whitelist_user_state.total_contributed.ge(&max_contribution) {
+            msg!(
+                "Whitelist {}: User already redeemed the full allocation",
+                instruction
+            );
+            return Err(AccountAlreadyRedeemed.into());
+        }
+
+        msg!(
+            "Whitelist {}: Purchase amount exceeds the user's remaining allocation",
+            instruction
+        );
+        return Err(AllocationExceeded.into());
+    }
+
+    // Checking if the purchase amount is above the minimum contribution, unless it is all
+    // that is left of the user's allocation
+    let fills_allocation = max_contribution > 0 && total_contributed == max_contribution;
+
+    if input_sol_amount.lt(&whitelist_global_state.min_contribution) && !fills_allocation {
+        msg!(
+            "Whitelist {}: Purchase amount is below the minimum contribution",
+            instruction
+        );
+        return Err(ContributionTooSmall.into());
+    }
+
+    // Checking if the sale has room left under its hard cap
+    let total_raised = whitelist_global_state
+        .total_raised
+        .checked_add(input_sol_amount)
+        .ok_or(MathOverflow)?;
+
+    if whitelist_global_state.hard_cap > 0 && total_raised.gt(&whitelist_global_state.hard_cap) {
+        msg!(
+            "Whitelist {}: Purchase amount exceeds the sale's remaining hard cap",
+            instruction
+        );
+        return Err(HardCapExceeded.into());
+    }
+
+    Ok((price_per_token_y, total_contributed))
+}
+
+// Unpacks the Y token account a purchase pays into, which has to be the user's vesting
+// vault in a vesting sale and a Y token account of the user otherwise
+fn check_user_wlst_token_account(
+    instruction: &str,
+    user_account: &AccountInfo,
+    user_wlst_token_account: &AccountInfo,
+    whitelist_global_state_account: &AccountInfo,
+    whitelist_global_state: &WhitelistPDAGlobalState,
+    program_id: &Pubkey,
+) -> Result<TokenState, ProgramError> {
+    if !user_wlst_token_account.owner.eq(&token_program_id()) {
+        msg!(
+            "Whitelist {}: Passed Token Y account is not a token account",
+            instruction
+        );
+        return Err(NotATokenAccount.into());
+    }
+
+    let user_wlst_token_state = TokenState::unpack(&user_wlst_token_account.data.borrow())?;
+
+    if whitelist_global_state.is_vesting {
+        let (vesting_vault_address, _) = find_whitelist_vesting_vault_program_address(
+            whitelist_global_state_account.key,
+            user_account.key,
+            program_id,
+        );
+
+        if !user_wlst_token_account.key.eq(&vesting_vault_address) {
+            msg!(
+                "Whitelist {}: Y Token account is not the user's vesting vault",
+                instruction
+            );
+            return Err(IncorrectStateAccount.into());
+        }
+
+        if !user_wlst_token_state
+            .owner
+            .eq(whitelist_global_state_account.key)
+        {
+            msg!(
+                "Whitelist {}: Vesting vault's owner is not the Whitelist PDA",
+                instruction
+            );
+            return Err(IncorrectTokenOwner.into());
+        }
+    } else if !user_wlst_token_state.owner.eq(user_account.key) {
+        msg!(
+            "Whitelist {}: Y Token's owner is not the passed user",
+            instruction
+        );
+        return Err(IncorrectUserTokenAccount.into());
+    }
+
+    if !user_wlst_token_state
+        .mint
+        .eq(&whitelist_global_state.y_mint_account)
+    {
+        msg!("Whitelist {}: User Token Y Mint mismatch", instruction);
+        return Err(MintMismatch.into());
+    }
+
+    Ok(user_wlst_token_state)
+}
+
+// Checks the price, contribution limits and sale window a sale is initialized with
+fn check_sale_config(
+    instruction: &str,
+    price_per_token_y: u64,
+    min_contribution: u64,
+    max_contribution: u64,
+    sale_start_ts: UnixTimestamp,
+    sale_end_ts: UnixTimestamp,
+) -> ProgramResult {
+    // Checking about the value of price per token B
+    if price_per_token_y == 0 {
+        msg!(
+            "Whitelist {}: Price per Token B should be greater than 0",
+            instruction
+        );
+        return Err(InvalidSaleConfig.into());
+    }
+
+    // Checking if the contribution limits are consistent (0 max means uncapped)
+    if max_contribution > 0 && min_contribution.gt(&max_contribution) {
+        msg!(
+            "Whitelist {}: Minimum contribution is above the maximum contribution",
+            instruction
+        );
+        return Err(InvalidSaleConfig.into());
+    }
+
+    check_sale_schedule(instruction, sale_start_ts, sale_end_ts, &[])
+}
+
+// Creates a PDA account of `space` bytes owned by `owner`, the payer funding its rent.
+// Lamports someone already sent to the predictable address count towards the rent
+// exemption, so pre-funding it neither blocks the creation nor gets topped up again.
+fn create_pda_account<'a>(
+    payer_account: &AccountInfo<'a>,
+    pda_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    pda_seeds_bump: &[&[u8]],
+    space: usize,
+    owner: &Pubkey,
+) -> ProgramResult {
+    let lamports_required = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(pda_account.lamports());
+
+    if pda_account.lamports() == 0 {
+        msg!("Creating PDA account {}", pda_account.key.to_string());
+        let create_pda_account_ix = system_instruction::create_account(
+            payer_account.key,
+            pda_account.key,
+            lamports_required,
+            space as u64,
+            owner,
+        );
+
+        return invoke_signed(
+            &create_pda_account_ix,
+            &[
+                payer_account.clone(),
+                pda_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[pda_seeds_bump],
+        );
+    }
+
+    // A funded address can't go through create_account, so topping it up and then
+    // allocating and assigning it one by one
+    if lamports_required > 0 {
+        msg!("Transferring lamports to PDA account to make rent exempt");
+        let transfer_lamports_to_pda_ix =
+            system_instruction::transfer(payer_account.key, pda_account.key, lamports_required);
+
+        invoke(
+            &transfer_lamports_to_pda_ix,
+            &[
+                payer_account.clone(),
+                pda_account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+    }
+
+    msg!(
+        "Allocating space for PDA account {}",
+        pda_account.key.to_string()
+    );
+    let allocate_space_pda_ix = system_instruction::allocate(pda_account.key, space as u64);
+
+    invoke_signed(
+        &allocate_space_pda_ix,
+        &[pda_account.clone(), system_program_account.clone()],
+        &[pda_seeds_bump],
+    )?;
+
+    msg!("Assigning the owner of the newly created PDA");
+    let assign_owner_to_pda_ix = system_instruction::assign(pda_account.key, owner);
+
+    invoke_signed(
+        &assign_owner_to_pda_ix,
+        &[pda_account.clone(), system_program_account.clone()],
+        &[pda_seeds_bump],
+    )
+}
+
+// Lists a newly created sale in its creator's registry, creating the registry on their first sale
+fn register_whitelist_sale<'a>(
+    instruction: &str,
+    whitelist_creator: &AccountInfo<'a>,
+    whitelist_registry_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    whitelist_global_state: &Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let (whitelist_registry_address, registry_bump) =
+        find_whitelist_registry_program_address(whitelist_creator.key, program_id);
+
+    if !whitelist_registry_account
+        .key
+        .eq(&whitelist_registry_address)
+    {
+        msg!(
+            "Whitelist {}: Creator registry PDA account mismatch",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    if whitelist_registry_account.owner.eq(&system_program_id()) {
+        let registry_seeds_bump: &[&[u8]] = &[
+            WHITELIST_REGISTRY_SEED,
+            &whitelist_creator.key.to_bytes(),
+            &[registry_bump],
+        ];
+
+        create_pda_account(
+            whitelist_creator,
+            whitelist_registry_account,
+            system_program_account,
+            registry_seeds_bump,
+            get_state_packed_len::<WhitelistCreatorRegistry>(),
+            program_id,
+        )?;
+    } else if !whitelist_registry_account.owner.eq(program_id) {
+        msg!(
+            "Whitelist {}: Passed creator registry account is invalid",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    let mut whitelist_registry =
+        WhitelistCreatorRegistry::try_from_slice(&whitelist_registry_account.data.borrow())?;
+
+    if !whitelist_registry.is_initialized() {
+        whitelist_registry.is_initialized = true;
+        whitelist_registry.whitelist_creator = *whitelist_creator.key;
+        whitelist_registry.registry_bump = registry_bump;
+    }
+
+    if whitelist_registry.sale_count as usize >= MAX_REGISTRY_SALES {
+        msg!(
+            "Whitelist {}: Creator already runs {} sales, close one first",
+            instruction,
+            MAX_REGISTRY_SALES
+        );
+        return Err(RegistryFull.into());
+    }
+
+    whitelist_registry.sales[whitelist_registry.sale_count as usize] = *whitelist_global_state;
+    whitelist_registry.sale_count += 1;
+
+    whitelist_registry.serialize(&mut &mut whitelist_registry_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Drops a closed sale from its creator's registry
+fn unregister_whitelist_sale(
+    instruction: &str,
+    whitelist_creator: &Pubkey,
+    whitelist_registry_account: &AccountInfo,
+    whitelist_global_state: &Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if !whitelist_registry_account.owner.eq(program_id) {
+        msg!(
+            "Whitelist {}: Passed creator registry account is invalid",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    let mut whitelist_registry =
+        WhitelistCreatorRegistry::try_from_slice(&whitelist_registry_account.data.borrow())?;
+
+    if !whitelist_registry.whitelist_creator.eq(whitelist_creator) {
+        msg!(
+            "Whitelist {}: Creator registry belongs to another creator",
+            instruction
+        );
+        return Err(IncorrectStateAccount.into());
+    }
+
+    // Moving the last sale into the freed slot keeps the listed sales contiguous
+    let sale_count = whitelist_registry.sale_count as usize;
+    if let Some(index) = whitelist_registry
+        .sales()
+        .iter()
+        .position(|sale| sale.eq(whitelist_global_state))
+    {
+        whitelist_registry.sales[index] = whitelist_registry.sales[sale_count - 1];
+        whitelist_registry.sales[sale_count - 1] = Pubkey::default();
+        whitelist_registry.sale_count -= 1;
+    }
+
+    whitelist_registry.serialize(&mut &mut whitelist_registry_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub struct WhiteListProcessor;
+
+impl WhiteListProcessor {
+    // INITIALIZE
+    #[allow(clippy::too_many_arguments)]
+    fn process_whitelist_initialize(
+        merkle_root: [u8; 32],
+        price_per_token_y: u64,
+        min_contribution: u64,
+        max_contribution: u64,
+        sale_start_ts: UnixTimestamp,
+        sale_end_ts: UnixTimestamp,
+        is_fixed_price: bool,
+        sale_id: u64,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        // All accounts
+        let accounts_iterable = &mut accounts.iter();
+        let whitelist_creator = next_account_info(accounts_iterable)?;
+        let whitelist_pda_account = next_account_info(accounts_iterable)?;
+        let token_swap_pool_state_account = next_account_info(accounts_iterable)?;
+        let y_token_mint_account = next_account_info(accounts_iterable)?;
+        let y_token_account = next_account_info(accounts_iterable)?;
+        let quote_token_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+        let whitelist_registry_account = next_account_info(accounts_iterable)?;
+
+        // Only the canonical bump is accepted, so a sale has a single global state address
+        let (whitelist_program_address, whitelist_pda_bump) = find_whitelist_program_address(
+            whitelist_creator.key,
+            y_token_account.key,
+            sale_id,
+            program_id,
+        );
+
+        let pda_seeds_bump: &[&[u8]] = &[
+            WHITELIST_PDA_SEED,
+            &whitelist_creator.key.to_bytes(),
+            &y_token_account.key.to_bytes(),
+            &sale_id.to_le_bytes(),
+            &[whitelist_pda_bump],
+        ];
+
+        let quote_mint = check_pool_binding(
+            "Initialize",
+            token_swap_pool_state_account,
+            y_token_mint_account,
+            y_token_account,
+            quote_token_account,
+        )?;
+
+        check_sale_config(
+            "Initialize",
+            price_per_token_y,
+            min_contribution,
+            max_contribution,
+            sale_start_ts,
+            sale_end_ts,
+        )?;
+
+        // A fixed price sale only holds up if the pool sells Y at that very price
+        if is_fixed_price {
+            check_fixed_price_pool(
+                "Initialize",
+                token_swap_pool_state_account,
+                y_token_account,
+                price_per_token_y,
+            )?;
+        }
+
+        // Checking if the resultant PDA address matches
+        if !whitelist_pda_account.key.eq(&whitelist_program_address) {
+            msg!("Whitelist Initialize: PDA accounts mismatch");
+            return Err(IncorrectPdaAccount.into());
+        }
+
+        // Checking if the pool owner has signed
+        if !whitelist_creator.is_signer {
+            msg!("Whitelist Initialize: Pool creator has not signed");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Checking if the system program id is the one expected
+        if !system_program_account.key.eq(&system_program_id()) {
+            msg!("Whitelist Initialize: System Program accounts mismatch");
+            return Err(IncorrectSystemProgram.into());
+        }
+
+        // Checking if the PDA has already been initialized, which the system program would
+        // only reject with an opaque error
+        if !whitelist_pda_account.owner.eq(&system_program_id()) {
+            msg!("Whitelist Initialize: PDA account has already been initialized");
+
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        create_pda_account(
+            whitelist_creator,
+            whitelist_pda_account,
+            system_program_account,
+            pda_seeds_bump,
+            get_state_packed_len::<WhitelistPDAGlobalState>(),
+            program_id,
+        )?;
+
+        // Assigning basic state now
+        let mut whitelist_pda_state =
+            WhitelistPDAGlobalState::try_from_slice(&whitelist_pda_account.data.borrow())?;
+
+        msg!(
+            "Initializing the PDA Account of address {}",
+            whitelist_program_address
+        );
+
+        whitelist_pda_state.version = GLOBAL_STATE_VERSION;
+        whitelist_pda_state.is_initialized = true;
+        whitelist_pda_state.whitelist_creator = *whitelist_creator.key;
+        whitelist_pda_state.global_pda_bump = whitelist_pda_bump;
+        whitelist_pda_state.whitelist_merkle_root = merkle_root;
+        whitelist_pda_state.quote_token_account = *quote_token_account.key;
+        whitelist_pda_state.token_swap_pool_state = *token_swap_pool_state_account.key;
+        whitelist_pda_state.y_mint_account = *y_token_mint_account.key;
+        whitelist_pda_state.y_token_account = *y_token_account.key;
+        whitelist_pda_state.price_per_token_y = price_per_token_y;
+        whitelist_pda_state.whitelist_authority = *whitelist_creator.key;
+        whitelist_pda_state.whitelist_admin = *whitelist_creator.key;
+        whitelist_pda_state.min_contribution = min_contribution;
+        whitelist_pda_state.max_contribution = max_contribution;
+        whitelist_pda_state.sale_start_ts = sale_start_ts;
+        whitelist_pda_state.sale_end_ts = sale_end_ts;
+        whitelist_pda_state.is_fixed_price = is_fixed_price;
+        whitelist_pda_state.treasury = *whitelist_creator.key;
+        whitelist_pda_state.seed_y_token_account = *y_token_account.key;
+        whitelist_pda_state.sale_id = sale_id;
+        whitelist_pda_state.quote_mint = quote_mint;
+
+        whitelist_pda_state.serialize(&mut &mut whitelist_pda_account.data.borrow_mut()[..])?;
+
+        register_whitelist_sale(
+            "Initialize",
+            whitelist_creator,
+            whitelist_registry_account,
+            system_program_account,
+            whitelist_pda_account.key,
+            program_id,
+        )?;
+
+        WhiteListEvent::SaleInitialized {
+            sale: *whitelist_pda_account.key,
+            creator: *whitelist_creator.key,
+            y_mint: *y_token_mint_account.key,
+            sale_id,
+            price_per_token_y,
+            is_direct_sale: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit();
+
+        msg!("Creation of Whitelist PDA complete successfully");
+
+        Ok(())
+    }
+
+    // PLAIN ASSOC WRAP SOL
+    fn process_whitelist_wrap_sol(
+        amount_to_be_wrapped: u64,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let funding_account = next_account_info(accounts_iterable)?;
+        let associated_token_pda_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+        let spl_token_program_account = next_account_info(accounts_iterable)?;
+
+        // Optional whitelist global state, when wrapping as part of the sale flow
+        if let Some(whitelist_global_state_account) = accounts_iterable.next() {
+            let whitelist_global_state = unpack_whitelist_global_state(
+                "WrapSOLToken",
+                whitelist_global_state_account,
+                program_id,
+            )?;
+
+            check_sale_not_paused("WrapSOLToken", &whitelist_global_state)?;
+        }
+
+        let associated_account_address =
+            get_associated_token_address(&funding_account.key, &native_mint_account());
+
+        // Processes a bunch of checks which are common for both create-wrap and wrap SOL
+
+        check_for_wrapping(
+            "WrapSOLToken",
+            amount_to_be_wrapped,
+            0,
+            &associated_account_address,
+            funding_account,
+            associated_token_pda_account,
+            spl_token_program_account,
+            system_program_account,
+        )?;
+
+        // Checking if the associated account already exists
+        if !associated_token_pda_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist WrapSOLToken: Associated token account does not exist");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        // Business logic starts
+
+        // Transferring the required lamports to be wrapped to the PDA
+        msg!("Transferring SOL to Associated account");
+        let transfer_lamports_to_assoc_account_ix = system_instruction::transfer(
+            &funding_account.key,
+            &associated_account_address,
+            amount_to_be_wrapped,
+        );
+
+        invoke(
+            &transfer_lamports_to_assoc_account_ix,
+            &[
+                funding_account.clone(),
+                associated_token_pda_account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+
+        // Sync Nativeing the associated account to update the wSOL balance
+        msg!("Sync Nativeing the associated account to update the wSOL balance");
+        let sync_native_sol_ix =
+            sync_native(&token_program_id(), &associated_token_pda_account.key)?;
+
+        invoke(
+            &sync_native_sol_ix,
+            &[
+                associated_token_pda_account.clone(),
+                spl_token_program_account.clone(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    // CREATE AND WRAP SOL
+    fn process_whitelist_create_and_wrap_sol(
+        amount_to_be_wrapped: u64,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let funding_account = next_account_info(accounts_iterable)?;
+        let associated_token_pda_account = next_account_info(accounts_iterable)?;
+        let native_token_mint_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+        let spl_token_program_account = next_account_info(accounts_iterable)?;
+        let associated_token_program_account = next_account_info(accounts_iterable)?;
+        let rent_sysvar_account = next_account_info(accounts_iterable)?;
+
+        // Optional whitelist global state, when wrapping as part of the sale flow
+        if let Some(whitelist_global_state_account) = accounts_iterable.next() {
+            let whitelist_global_state = unpack_whitelist_global_state(
+                "CreateAndWrapSOLToken",
+                whitelist_global_state_account,
+                program_id,
+            )?;
+
+            check_sale_not_paused("CreateAndWrapSOLToken", &whitelist_global_state)?;
+        }
+
+        let associated_account_address =
+            get_associated_token_address(&funding_account.key, &native_mint_account());
+
+        // Processes a bunch of checks which are common for both create-wrap and wrap SOL
+
+        check_for_wrapping(
+            "CreateAndWrapSOLToken",
+            amount_to_be_wrapped,
+            Rent::get()?.minimum_balance(TokenState::LEN),
+            &associated_account_address,
+            funding_account,
+            associated_token_pda_account,
+            spl_token_program_account,
+            system_program_account,
+        )?;
+
+        // Checking if the right spl associated token program account has been passed
+        if !associated_token_program_account
+            .key
+            .eq(&associated_token_program_id())
+        {
+            msg!("WhiteList CreateAndWrapSOLToken: Passed Associated Token Program key mismatches with original Token Program key");
+            return Err(IncorrectAssociatedTokenProgram.into());
+        }
+
+        // Checking if the corrent native mint account has been passed
+        if !native_token_mint_account.key.eq(&native_mint_account()) {
+            msg!("WhiteList CreateAndWrapSOLToken: Passed Native Mint account mismatch with original native mint account");
+            return Err(MintMismatch.into());
+        }
+
+        // Checking if the pda account is not initialized
+        if !associated_token_pda_account.owner.eq(&system_program_id()) {
+            msg!("WhiteList CreateAndWrapSOLToken: Associated account already exists");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        // Business logic starts
+
+        // Creating a Associated token account for the funded account first, the associated
+        // token program only tops up whatever rent a pre-funded address is still missing
+        msg!("Creating an Associated Token Account for the funded account");
+        let create_associated_token_for_funded_account_ix = create_associated_token_account(
+            funding_account.key,
+            funding_account.key,
+            &native_mint_account(),
+            &token_program_id(),
+        );
+
+        invoke(
+            &create_associated_token_for_funded_account_ix,
+            &[
+                funding_account.clone(),
+                associated_token_pda_account.clone(),
+                funding_account.clone(),
+                native_token_mint_account.clone(),
+                system_program_account.clone(),
+                spl_token_program_account.clone(),
+                rent_sysvar_account.clone(),
+                associated_token_program_account.clone(),
+            ],
+        )?;
+
+        // Transferring the required lamports to be wrapped to the associated account
+        msg!("Transferring SOL to Associated account");
+        let transfer_lamports_to_assoc_account_ix = system_instruction::transfer(
+            &funding_account.key,
+            &associated_account_address,
             amount_to_be_wrapped,
         );
 
         invoke(
-            &transfer_lamports_to_assoc_account_ix,
+            &transfer_lamports_to_assoc_account_ix,
+            &[
+                funding_account.clone(),
+                associated_token_pda_account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+
+        // Sync Nativeing the associated account to update the wSOL balance, which also
+        // counts any lamports the address held on top of its rent exemption
+        msg!("Sync Nativeing the associated account to update the wSOL balance");
+        let sync_native_sol_ix =
+            sync_native(&token_program_id(), &associated_token_pda_account.key)?;
+
+        invoke(
+            &sync_native_sol_ix,
+            &[
+                associated_token_pda_account.clone(),
+                spl_token_program_account.clone(),
+            ],
+        )?;
+
+        msg!("DONE :)");
+
+        Ok(())
+    }
+
+    // UNWRAP SOL
+    fn process_whitelist_unwrap_sol(accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let owner_account = next_account_info(accounts_iterable)?;
+        let native_assoc_pda_account = next_account_info(accounts_iterable)?;
+        let spl_token_program_account = next_account_info(accounts_iterable)?;
+
+        let associated_account_address =
+            get_associated_token_address(&owner_account.key, &native_mint_account());
+
+        // Checking if the owner account has signed
+        if !owner_account.is_signer {
+            msg!("Whitelist UnwrapSOLToken: Owner account has not signed");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Checking if the spl program matches
+        if !spl_token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist UnwrapSOLToken: SPL Program Id mismatch with original program id");
+            return Err(IncorrectTokenProgram.into());
+        }
+
+        // Checking if the token account is a "token" account
+        if !native_assoc_pda_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist UnwrapSOLToken: Passed account is not a token account");
+            return Err(NotATokenAccount.into());
+        }
+
+        // Checking if the token account is an associated account
+        if !native_assoc_pda_account.key.eq(&associated_account_address) {
+            msg!("Whitelist UnwrapSOLToken: Passed token account is not an associated account");
+            return Err(AssociatedAccountMismatch.into());
+        }
+
+        // Checking if the associated token account had any balance
+        if native_assoc_pda_account.lamports().eq(&0u64) {
+            msg!("Whitelist UnwrapSOLToken: Associated Token account contains 0 SOL to unwrap");
+            return Err(InsufficientSol.into());
+        }
+
+        // Business logic starts
+
+        // Closing the entire associated account such that we can return back the SOL balance to the funded account
+        msg!("Closing the associated account");
+        let associated_account_close_ix = close_account(
+            &token_program_id(),
+            &native_assoc_pda_account.key,
+            &owner_account.key,
+            &owner_account.key,
+            &[&owner_account.key],
+        )?;
+
+        invoke(
+            &associated_account_close_ix,
+            &[
+                native_assoc_pda_account.clone(),
+                owner_account.clone(),
+                owner_account.clone(),
+            ],
+        )?;
+
+        msg!("Done :)");
+
+        Ok(())
+    }
+
+    // SWAP SOL FOR SPL TOKEN
+    fn process_whitelist_swap_sol(
+        input_sol_amount: u64,
+        expected_spl_token_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // Whitelist program related accounts
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+
+        // Token swap related accounts
+        let token_swap_state_account = next_account_info(accounts_iterable)?;
+        let swap_authority_pda_account = next_account_info(accounts_iterable)?;
+        let user_temporary_auth_token_account = next_account_info(accounts_iterable)?;
+        let user_quote_token_account = next_account_info(accounts_iterable)?;
+        let user_wlst_token_account = next_account_info(accounts_iterable)?;
+        let token_swap_quote_token_account = next_account_info(accounts_iterable)?;
+        let token_swap_wlst_token_account = next_account_info(accounts_iterable)?;
+        let pool_mint_token_account = next_account_info(accounts_iterable)?;
+        let pool_token_fee_account = next_account_info(accounts_iterable)?;
+        let pool_owner_account = next_account_info(accounts_iterable)?;
+
+        // Program IDs
+        let token_program_account = next_account_info(accounts_iterable)?;
+        let token_swap_program_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+
+        // Optional whitelist entry account, checked instead of the Merkle proof
+        let whitelist_entry_account = accounts_iterable.next();
+
+        let current_network_time = Clock::get()?.unix_timestamp;
+
+        // Checking if the user has signed
+        if !user_account.is_signer {
+            msg!("Whitelist SwapSOL: User not signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Checking the program id equality (token swap and token programs)
+        if !token_swap_program_account.key.eq(&token_swap_program_id()) {
+            msg!("Whitelist SwapSOL: Incorrect Token Swap Program ID");
+            return Err(IncorrectSwapProgram.into());
+        }
+
+        if !token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist SwapSOL: Incorrect Token Program ID");
+            return Err(IncorrectTokenProgram.into());
+        }
+
+        // Checking if the whitelist accouts are all part of this program
+        if !whitelist_global_state_account.owner.eq(program_id) {
+            msg!("Whitelist SwapSOL: Passed Whitelist global state aaccount is invalid");
+            return Err(IncorrectStateAccount.into());
+        }
+
+        let (mut whitelist_user_state, user_state_bump) = load_whitelist_user_state(
+            "SwapSOL",
+            user_account,
+            whitelist_user_state_account,
+            whitelist_global_state_account,
+            system_program_account,
+            program_id,
+        )?;
+
+        // Checking the same as above, but for token and token swap accounts
+        if !token_swap_state_account.owner.eq(&token_swap_program_id()) {
+            msg!("Whitelist SwapSOL: Passed TokenSwap state aaccount is invalid");
+            return Err(IncorrectStateAccount.into());
+        }
+
+        if !user_quote_token_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist SwapSOL: Passed Quote Token account is not a token account");
+            return Err(NotATokenAccount.into());
+        }
+
+        if !token_swap_quote_token_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist SwapSOL: Passed TokenSwap Quote Token account is not a token account");
+            return Err(NotATokenAccount.into());
+        }
+
+        if !token_swap_wlst_token_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist SwapSOL: Passed TokenSwap Token Y account is not a token account");
+            return Err(NotATokenAccount.into());
+        }
+
+        if !pool_mint_token_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist SwapSOL: Passed TokenSwap pool mint account is not a token account");
+            return Err(NotATokenAccount.into());
+        }
+
+        if !pool_token_fee_account.owner.eq(&token_program_id()) {
+            msg!(
+                "Whitelist SwapSOL: Passed TokenSwap Pool Fee Token account is not a token account"
+            );
+            return Err(NotATokenAccount.into());
+        }
+
+        // All account states
+        let mut whitelist_global_state =
+            WhitelistPDAGlobalState::unpack(&whitelist_global_state_account.data.borrow())?;
+
+        let user_quote_token_state = TokenState::unpack(&user_quote_token_account.data.borrow())?;
+
+        let user_wlst_token_state = check_user_wlst_token_account(
+            "SwapSOL",
+            user_account,
+            user_wlst_token_account,
+            whitelist_global_state_account,
+            &whitelist_global_state,
+            program_id,
+        )?;
+
+        // Checking if the quote token account's owner is the user
+        if !user_quote_token_state.owner.eq(&user_account.key) {
+            msg!("Whitelist SwapSOL: Quote Token's owner is not the passed user");
+
+            return Err(IncorrectUserTokenAccount.into());
+        }
+
+        // Checking if the quote token account has the sale's quote mint
+        if !user_quote_token_state
+            .mint
+            .eq(&whitelist_global_state.quote_mint)
+        {
+            msg!("Whitelist SwapSOL: Passed token account's mint is not the sale's quote mint");
+            return Err(MintMismatch.into());
+        }
+
+        // Checking if the Whitelist global state account has been initialized
+        if !whitelist_global_state.is_initialized() {
+            msg!("Whitelist SwapSOL: Global state not initialized yet");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        check_whitelist_global_address(
+            "SwapSOL",
+            whitelist_global_state_account,
+            &whitelist_global_state,
+            program_id,
+        )?;
+
+        if whitelist_global_state.is_direct_sale {
+            msg!("Whitelist SwapSOL: Direct sales are bought with BuyDirect");
+            return Err(SaleModeMismatch.into());
+        }
+
+        let (price_per_token_y, total_contributed) = check_purchase(
+            "SwapSOL",
+            input_sol_amount,
+            allocation,
+            &proof,
+            current_network_time,
+            user_account,
+            whitelist_global_state_account,
+            &whitelist_global_state,
+            &whitelist_user_state,
+            whitelist_entry_account,
+            program_id,
+        )?;
+
+        // Users who bought before the migration have no user state, so sales migrated from
+        // version 1 keep checking that the user's Y token account is empty
+        if whitelist_global_state.has_legacy_seeds && user_wlst_token_state.amount.gt(&0u64) {
+            msg!("Whitelist SwapSOL: User already redeemed SPL");
+            return Err(AccountAlreadyRedeemed.into());
+        }
+
+        // Checking if the user's quote token account has enough balance
+        if user_quote_token_state.amount.lt(&input_sol_amount) {
+            msg!("Whitelist SwapSOL: Insufficient quote tokens recognized");
+
+            if whitelist_global_state.quote_mint.eq(&native_mint_account()) {
+                return Err(InsufficientSol.into());
+            }
+            return Err(InsufficientFunds.into());
+        }
+
+        // In fixed price mode the program decides how many Y tokens the SOL buys
+        let minimum_amount_out = if whitelist_global_state.is_fixed_price {
+            let fixed_price_spl_token_amount = input_sol_amount
+                .checked_div(price_per_token_y)
+                .ok_or(MathOverflow)?;
+
+            if fixed_price_spl_token_amount == 0 {
+                msg!("Whitelist SwapSOL: Swap amount does not buy a single Y token");
+                return Err(ContributionTooSmall.into());
+            }
+
+            // The pool takes the whole swap amount, so it has to pay for whole Y tokens only
+            if fixed_price_spl_token_amount.checked_mul(price_per_token_y) != Some(input_sol_amount)
+            {
+                msg!("Whitelist SwapSOL: Swap amount is not a multiple of the token price");
+                return Err(AmountNotPriceMultiple.into());
+            }
+
+            if expected_spl_token_amount.gt(&fixed_price_spl_token_amount) {
+                msg!("Whitelist SwapSOL: Expected Y token amount is above the fixed price amount");
+                return Err(PriceMismatch.into());
+            }
+
+            fixed_price_spl_token_amount
+        } else {
+            expected_spl_token_amount
+        };
+
+        // Checking if the Whitelist Global state's token swap state account matches with the passed account
+
+        if !token_swap_state_account
+            .key
+            .eq(&whitelist_global_state.token_swap_pool_state)
+        {
+            msg!("Whitelist SwapSOL: Token Swap State Account mismatch");
+            return Err(PoolMismatch.into());
+        }
+
+        msg!("Starting the Swap procedure");
+        let quote_to_spl_swap_ix = swap(
+            &token_swap_program_id(),
+            &token_program_id(),
+            &token_swap_state_account.key,
+            &swap_authority_pda_account.key,
+            &user_temporary_auth_token_account.key,
+            &user_quote_token_account.key,
+            &token_swap_quote_token_account.key,
+            &token_swap_wlst_token_account.key,
+            &user_wlst_token_account.key,
+            &pool_mint_token_account.key,
+            &pool_token_fee_account.key,
+            Some(&pool_owner_account.key),
+            Swap {
+                amount_in: input_sol_amount,
+                minimum_amount_out,
+            },
+        )?;
+
+        invoke(
+            &quote_to_spl_swap_ix,
+            &[
+                token_swap_state_account.clone(),
+                swap_authority_pda_account.clone(),
+                user_temporary_auth_token_account.clone(),
+                user_quote_token_account.clone(),
+                token_swap_quote_token_account.clone(),
+                token_swap_wlst_token_account.clone(),
+                user_wlst_token_account.clone(),
+                pool_mint_token_account.clone(),
+                pool_token_fee_account.clone(),
+                token_program_account.clone(),
+                pool_owner_account.clone(),
+                token_swap_program_account.clone(),
+            ],
+        )?;
+
+        // Measuring how many Y tokens the swap actually delivered
+        let user_wlst_token_amount_after =
+            TokenState::unpack(&user_wlst_token_account.data.borrow())?.amount;
+        let received_spl_token_amount = user_wlst_token_amount_after
+            .checked_sub(user_wlst_token_state.amount)
+            .ok_or(MathOverflow)?;
+
+        // Checking if the pool delivered exactly the fixed price amount
+        if whitelist_global_state.is_fixed_price && received_spl_token_amount != minimum_amount_out
+        {
+            msg!(
+                "Whitelist SwapSOL: Pool returned {} Y tokens instead of {} at the fixed price",
+                received_spl_token_amount.to_string(),
+                minimum_amount_out.to_string()
+            );
+            return Err(PriceMismatch.into());
+        }
+
+        msg!("Assigning state for the whitelist user");
+        if !whitelist_user_state.is_initialized() {
+            whitelist_user_state.is_initialized = true;
+            whitelist_user_state.whitelisted_by_account = *user_account.key;
+            whitelist_user_state.whitelisted_at = current_network_time;
+            whitelist_user_state.user_state_bump = user_state_bump;
+        }
+        whitelist_user_state.user_transfer_authority_account =
+            *user_temporary_auth_token_account.key;
+        whitelist_user_state.total_contributed = total_contributed;
+        whitelist_user_state.total_received = whitelist_user_state
+            .total_received
+            .checked_add(received_spl_token_amount)
+            .ok_or(MathOverflow)?;
+
+        whitelist_user_state
+            .serialize(&mut &mut whitelist_user_state_account.data.borrow_mut()[..])?;
+
+        whitelist_global_state.total_raised = whitelist_global_state
+            .total_raised
+            .checked_add(input_sol_amount)
+            .ok_or(MathOverflow)?;
+
+        if whitelist_global_state.is_vesting {
+            whitelist_global_state.total_y_vesting = whitelist_global_state
+                .total_y_vesting
+                .checked_add(received_spl_token_amount)
+                .ok_or(MathOverflow)?;
+        }
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        WhiteListEvent::Purchased {
+            sale: *whitelist_global_state_account.key,
+            user: *user_account.key,
+            sol_amount: input_sol_amount,
+            y_amount: received_spl_token_amount,
+            price_per_token_y,
+            timestamp: current_network_time,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // ADD TO WHITELIST
+    fn process_whitelist_add_entry(
+        allocation: u64,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let admin_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_entry_account = next_account_info(accounts_iterable)?;
+        let user_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+        let funding_account = next_account_info(accounts_iterable)?;
+
+        let whitelist_global_state = unpack_whitelist_global_state(
+            "AddToWhitelist",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_delegate(
+            "AddToWhitelist",
+            admin_account,
+            &whitelist_global_state,
+            &whitelist_global_state.whitelist_admin,
+            accounts_iterable.as_slice(),
+        )?;
+
+        // Checking if the system program id is the one expected
+        if !system_program_account.key.eq(&system_program_id()) {
+            msg!("Whitelist AddToWhitelist: System Program accounts mismatch");
+            return Err(IncorrectSystemProgram.into());
+        }
+
+        let (whitelist_entry_address, entry_bump) = find_whitelist_entry_program_address(
+            whitelist_global_state_account.key,
+            user_account.key,
+            program_id,
+        );
+
+        let entry_seeds_bump: &[&[u8]] = &[
+            WHITELIST_ENTRY_SEED,
+            &whitelist_global_state_account.key.to_bytes(),
+            &user_account.key.to_bytes(),
+            &[entry_bump],
+        ];
+
+        // Checking if the resultant PDA address matches
+        if !whitelist_entry_account.key.eq(&whitelist_entry_address) {
+            msg!("Whitelist AddToWhitelist: Entry PDA account mismatch");
+            return Err(IncorrectEntryAccount.into());
+        }
+
+        // Checking if the user has already been whitelisted
+        if !whitelist_entry_account.owner.eq(&system_program_id()) {
+            msg!("Whitelist AddToWhitelist: User has already been whitelisted");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        create_pda_account(
+            funding_account,
+            whitelist_entry_account,
+            system_program_account,
+            entry_seeds_bump,
+            get_state_packed_len::<WhitelistEntryState>(),
+            program_id,
+        )?;
+
+        let whitelist_entry_state = WhitelistEntryState {
+            is_initialized: true,
+            whitelist_global_state: *whitelist_global_state_account.key,
+            user: *user_account.key,
+            allocation,
+            entry_bump,
+        };
+
+        whitelist_entry_state.serialize(&mut &mut whitelist_entry_account.data.borrow_mut()[..])?;
+
+        msg!("Whitelisted user {}", user_account.key.to_string());
+
+        Ok(())
+    }
+
+    // REMOVE FROM WHITELIST
+    fn process_whitelist_remove_entry(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let admin_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_entry_account = next_account_info(accounts_iterable)?;
+        let destination_account = next_account_info(accounts_iterable)?;
+
+        let whitelist_global_state = unpack_whitelist_global_state(
+            "RemoveFromWhitelist",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_delegate(
+            "RemoveFromWhitelist",
+            admin_account,
+            &whitelist_global_state,
+            &whitelist_global_state.whitelist_admin,
+            accounts_iterable.as_slice(),
+        )?;
+
+        let whitelist_entry_state = unpack_whitelist_entry(
+            "RemoveFromWhitelist",
+            whitelist_entry_account,
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        msg!(
+            "Removing user {} from the whitelist",
+            whitelist_entry_state.user.to_string()
+        );
+
+        close_program_account(whitelist_entry_account, destination_account)
+    }
+
+    // SET WHITELIST ADMIN
+    fn process_whitelist_set_admin(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let new_admin_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "SetWhitelistAdmin",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "SetWhitelistAdmin",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        whitelist_global_state.whitelist_admin = *new_admin_account.key;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist admin set to {}",
+            new_admin_account.key.to_string()
+        );
+
+        Ok(())
+    }
+
+    // SET SALE SCHEDULE
+    fn process_whitelist_set_sale_schedule(
+        sale_start_ts: UnixTimestamp,
+        sale_end_ts: UnixTimestamp,
+        phases: Vec<SalePhase>,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "SetSaleSchedule",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "SetSaleSchedule",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        // Checking if the sale hasn't raised anything yet, so buyers can rely on its window
+        // and phases, and on its end to get their refund or their tokens
+        if whitelist_global_state.total_raised > 0 {
+            msg!("Whitelist SetSaleSchedule: Schedule can't change once the sale has raised SOL");
+            return Err(SaleTermsLocked.into());
+        }
+
+        // The end of a sale is final, user states being closable from then on
+        let current_network_time = Clock::get()?.unix_timestamp;
+
+        if whitelist_global_state.has_ended(current_network_time) {
+            msg!("Whitelist SetSaleSchedule: Sale has already ended");
+            return Err(SaleEnded.into());
+        }
+
+        check_sale_schedule("SetSaleSchedule", sale_start_ts, sale_end_ts, &phases)?;
+
+        let mut sale_phases = [SalePhase::default(); MAX_SALE_PHASES];
+        sale_phases[..phases.len()].copy_from_slice(&phases);
+
+        whitelist_global_state.sale_start_ts = sale_start_ts;
+        whitelist_global_state.sale_end_ts = sale_end_ts;
+        whitelist_global_state.sale_phase_count = phases.len() as u8;
+        whitelist_global_state.sale_phases = sale_phases;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!("Sale schedule set with {} phases", phases.len().to_string());
+
+        Ok(())
+    }
+
+    // UPDATE CONFIG
+    fn process_whitelist_update_config(
+        price_per_token_y: u64,
+        is_fixed_price: bool,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let token_swap_pool_state_account = next_account_info(accounts_iterable)?;
+        let y_token_mint_account = next_account_info(accounts_iterable)?;
+        let y_token_account = next_account_info(accounts_iterable)?;
+        let quote_token_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "UpdateWhitelistConfig",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "UpdateWhitelistConfig",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        if whitelist_global_state.is_direct_sale {
+            msg!("Whitelist UpdateWhitelistConfig: Direct sales are not bound to a pool");
+            return Err(SaleModeMismatch.into());
+        }
+
+        let quote_mint = check_pool_binding(
+            "UpdateWhitelistConfig",
+            token_swap_pool_state_account,
+            y_token_mint_account,
+            y_token_account,
+            quote_token_account,
+        )?;
+
+        // Contributions already made are accounted in the quote mint, so it can't change
+        if !quote_mint.eq(&whitelist_global_state.quote_mint) {
+            msg!(
+                "Whitelist UpdateWhitelistConfig: New pool is not quoted in the sale's quote mint"
+            );
+            return Err(MintMismatch.into());
+        }
+
+        // Buyers, their vesting vaults and the vault of escrowed tokens hold the sale's Y mint
+        if !y_token_mint_account
+            .key
+            .eq(&whitelist_global_state.y_mint_account)
+        {
+            msg!("Whitelist UpdateWhitelistConfig: New pool does not sell the sale's Y mint");
+            return Err(MintMismatch.into());
+        }
+
+        // Checking about the value of price per token B
+        if price_per_token_y == 0 {
+            msg!("Whitelist UpdateWhitelistConfig: Price per Token B should be greater than 0");
+            return Err(InvalidSaleConfig.into());
+        }
+
+        // A fixed price sale only holds up if the pool sells Y at that very price
+        if is_fixed_price {
+            check_fixed_price_pool(
+                "UpdateWhitelistConfig",
+                token_swap_pool_state_account,
+                y_token_account,
+                price_per_token_y,
+            )?;
+        }
+
+        whitelist_global_state.price_per_token_y = price_per_token_y;
+        whitelist_global_state.is_fixed_price = is_fixed_price;
+        whitelist_global_state.token_swap_pool_state = *token_swap_pool_state_account.key;
+        whitelist_global_state.y_token_account = *y_token_account.key;
+        whitelist_global_state.quote_token_account = *quote_token_account.key;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist config updated, bound to pool {}",
+            token_swap_pool_state_account.key.to_string()
+        );
+
+        WhiteListEvent::ConfigUpdated {
+            sale: *whitelist_global_state_account.key,
+            token_swap_pool_state: *token_swap_pool_state_account.key,
+            price_per_token_y,
+            is_fixed_price,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // PAUSE / RESUME
+    fn process_whitelist_set_paused(
+        is_paused: bool,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let instruction = if is_paused { "Pause" } else { "Resume" };
+
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let authority_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state =
+            unpack_whitelist_global_state(instruction, whitelist_global_state_account, program_id)?;
+
+        // Checking if the authority or the guardian has signed
+        check_whitelist_delegate(
+            instruction,
+            authority_account,
+            &whitelist_global_state,
+            &whitelist_global_state.whitelist_guardian,
+            accounts_iterable.as_slice(),
+        )?;
+
+        whitelist_global_state.is_paused = is_paused;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!("Whitelist sale paused: {}", is_paused.to_string());
+
+        Ok(())
+    }
+
+    // SET GUARDIAN
+    fn process_whitelist_set_guardian(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let guardian_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "SetGuardian",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "SetGuardian",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        whitelist_global_state.whitelist_guardian = *guardian_account.key;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist guardian set to {}",
+            guardian_account.key.to_string()
+        );
+
+        Ok(())
+    }
+
+    // PROPOSE AUTHORITY
+    fn process_whitelist_propose_authority(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let proposed_authority_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "ProposeAuthority",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "ProposeAuthority",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        whitelist_global_state.pending_authority = *proposed_authority_account.key;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist authority proposed to {}",
+            proposed_authority_account.key.to_string()
+        );
+
+        Ok(())
+    }
+
+    // ACCEPT AUTHORITY
+    fn process_whitelist_accept_authority(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let pending_authority_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "AcceptAuthority",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // Checking if there is a pending proposal for the signer
+        if whitelist_global_state.pending_authority == Pubkey::default()
+            || !pending_authority_account
+                .key
+                .eq(&whitelist_global_state.pending_authority)
+        {
+            msg!("Whitelist AcceptAuthority: Signer is not the proposed authority");
+            return Err(IncorrectWhitelistAdmin.into());
+        }
+
+        validate_signer(
+            "AcceptAuthority",
+            pending_authority_account,
+            accounts_iterable.as_slice(),
+        )?;
+
+        whitelist_global_state.whitelist_authority = whitelist_global_state.pending_authority;
+        whitelist_global_state.pending_authority = Pubkey::default();
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist authority transferred to {}",
+            pending_authority_account.key.to_string()
+        );
+
+        Ok(())
+    }
+
+    // CLOSE WHITELIST
+    fn process_whitelist_close(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let destination_account = next_account_info(accounts_iterable)?;
+        let whitelist_registry_account = next_account_info(accounts_iterable)?;
+        let y_token_account = next_account_info(accounts_iterable)?;
+
+        let whitelist_global_state = unpack_whitelist_global_state(
+            "CloseWhitelist",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // A pool sale also passes its pool and the pool token account of the whitelist
+        let pool_accounts = if whitelist_global_state.is_direct_sale {
+            None
+        } else {
+            Some((
+                next_account_info(accounts_iterable)?,
+                next_account_info(accounts_iterable)?,
+            ))
+        };
+
+        check_whitelist_authority(
+            "CloseWhitelist",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        // Checking if the sale is over
+        let current_network_time = Clock::get()?.unix_timestamp;
+
+        if !whitelist_global_state.has_ended(current_network_time) {
+            msg!("Whitelist CloseWhitelist: Sale has not ended yet");
+            return Err(SaleNotEnded.into());
+        }
+
+        // Checking if every user got their refund, their escrowed or their vested tokens
+        let has_outstanding_claims = if whitelist_global_state.is_below_soft_cap() {
+            whitelist_global_state.total_refunded < whitelist_global_state.total_raised
+        } else {
+            whitelist_global_state.total_y_escrowed > 0
+                || whitelist_global_state.total_y_vesting > 0
+        };
+
+        if has_outstanding_claims {
+            msg!("Whitelist CloseWhitelist: Users still have refunds or tokens to claim");
+            return Err(ClaimsOutstanding.into());
+        }
+
+        if !y_token_account
+            .key
+            .eq(&whitelist_global_state.y_token_account)
+        {
+            msg!("Whitelist CloseWhitelist: Y Token account mismatch");
+            return Err(VaultMismatch.into());
+        }
+
+        // Only the PDA can move the tokens left in the vault of a direct sale
+        if whitelist_global_state.is_direct_sale
+            && TokenState::unpack(&y_token_account.data.borrow())?.amount > 0
+        {
+            msg!("Whitelist CloseWhitelist: Y Token vault still holds unsold tokens");
+            return Err(VaultNotEmpty.into());
+        }
+
+        // The proceeds have to be withdrawn to the treasury first, the destination only
+        // getting the rent back
+        match pool_accounts {
+            None => {
+                let rent_exempt_lamports =
+                    Rent::get()?.minimum_balance(whitelist_global_state_account.data_len());
+
+                if whitelist_global_state_account
+                    .lamports()
+                    .gt(&rent_exempt_lamports)
+                {
+                    msg!("Whitelist CloseWhitelist: Global state still holds direct sale proceeds");
+                    return Err(ProceedsOutstanding.into());
+                }
+            }
+            Some((token_swap_state_account, whitelist_pool_token_account)) => {
+                if !token_swap_state_account
+                    .key
+                    .eq(&whitelist_global_state.token_swap_pool_state)
+                {
+                    msg!("Whitelist CloseWhitelist: Token Swap State Account mismatch");
+                    return Err(PoolMismatch.into());
+                }
+
+                if !whitelist_pool_token_account.owner.eq(&token_program_id()) {
+                    msg!("Whitelist CloseWhitelist: Passed Pool Token account is not a Token Program Account");
+                    return Err(NotATokenAccount.into());
+                }
+
+                let pool_state = SwapVersion::unpack(&token_swap_state_account.data.borrow())?;
+                let whitelist_pool_token_state =
+                    TokenState::unpack(&whitelist_pool_token_account.data.borrow())?;
+
+                if !whitelist_pool_token_state
+                    .owner
+                    .eq(whitelist_global_state_account.key)
+                {
+                    msg!("Whitelist CloseWhitelist: Pool Token Account's owner is not the Whitelist PDA");
+                    return Err(IncorrectTokenOwner.into());
+                }
+
+                if !whitelist_pool_token_state.mint.eq(pool_state.pool_mint()) {
+                    msg!(
+                        "Whitelist CloseWhitelist: Pool Token Account's mint is not the pool mint"
+                    );
+                    return Err(MintMismatch.into());
+                }
+
+                if whitelist_pool_token_state.amount > 0 {
+                    msg!("Whitelist CloseWhitelist: Pool Token Account still holds liquidity");
+                    return Err(ProceedsOutstanding.into());
+                }
+            }
+        }
+
+        // Sales migrated from version 1 predate the registry and were never listed in it
+        if !whitelist_global_state.has_legacy_seeds {
+            unregister_whitelist_sale(
+                "CloseWhitelist",
+                &whitelist_global_state.whitelist_creator,
+                whitelist_registry_account,
+                whitelist_global_state_account.key,
+                program_id,
+            )?;
+        }
+
+        msg!(
+            "Closing the whitelist global state {}",
+            whitelist_global_state_account.key.to_string()
+        );
+
+        close_program_account(whitelist_global_state_account, destination_account)
+    }
+
+    // CLOSE USER STATE
+    fn process_whitelist_close_user_state(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let destination_account = next_account_info(accounts_iterable)?;
+
+        // Checking if the user has signed
+        if !user_account.is_signer {
+            msg!("Whitelist CloseUserState: User not signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let whitelist_user_state = unpack_whitelist_user_state(
+            "CloseUserState",
+            user_account,
+            whitelist_user_state_account,
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // Checking if the sale is over, either by its end time or by the whitelist being closed
+        let is_whitelist_closed = !whitelist_global_state_account.owner.eq(program_id)
+            || whitelist_global_state_account.lamports() == 0;
+
+        if !is_whitelist_closed {
+            let whitelist_global_state =
+                WhitelistPDAGlobalState::unpack(&whitelist_global_state_account.data.borrow())?;
+
+            check_whitelist_global_address(
+                "CloseUserState",
+                whitelist_global_state_account,
+                &whitelist_global_state,
+                program_id,
+            )?;
+
+            let current_network_time = Clock::get()?.unix_timestamp;
+
+            if !whitelist_global_state.has_ended(current_network_time) {
+                msg!("Whitelist CloseUserState: Sale has not ended yet");
+                return Err(SaleNotEnded.into());
+            }
+
+            // Checking if the user got their refund, their escrowed or their vested tokens
+            let has_outstanding_claims = if whitelist_global_state.is_below_soft_cap() {
+                whitelist_user_state.total_refunded < whitelist_user_state.total_contributed
+            } else {
+                (whitelist_global_state.is_claim_later || whitelist_global_state.is_vesting)
+                    && whitelist_user_state.total_claimed < whitelist_user_state.total_received
+            };
+
+            if has_outstanding_claims {
+                msg!("Whitelist CloseUserState: User still has a refund or tokens to claim");
+                return Err(ClaimsOutstanding.into());
+            }
+        }
+
+        msg!(
+            "Closing the whitelist user state {}",
+            whitelist_user_state_account.key.to_string()
+        );
+
+        close_program_account(whitelist_user_state_account, destination_account)
+    }
+
+    // SET TREASURY
+    fn process_whitelist_set_treasury(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let treasury_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "SetTreasury",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "SetTreasury",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        whitelist_global_state.treasury = *treasury_account.key;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist treasury set to {}",
+            treasury_account.key.to_string()
+        );
+
+        Ok(())
+    }
+
+    // WITHDRAW PROCEEDS
+    fn process_whitelist_withdraw_proceeds(
+        amount: u64,
+        maximum_pool_token_amount: u64,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let token_swap_state_account = next_account_info(accounts_iterable)?;
+        let swap_authority_pda_account = next_account_info(accounts_iterable)?;
+        let pool_mint_token_account = next_account_info(accounts_iterable)?;
+        let whitelist_pool_token_account = next_account_info(accounts_iterable)?;
+        let token_swap_quote_token_account = next_account_info(accounts_iterable)?;
+        let token_swap_wlst_token_account = next_account_info(accounts_iterable)?;
+        let proceeds_token_account = next_account_info(accounts_iterable)?;
+        let pool_token_fee_account = next_account_info(accounts_iterable)?;
+        let treasury_account = next_account_info(accounts_iterable)?;
+        let token_program_account = next_account_info(accounts_iterable)?;
+        let token_swap_program_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "WithdrawProceeds",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "WithdrawProceeds",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        if whitelist_global_state.is_direct_sale {
+            msg!("Whitelist WithdrawProceeds: Direct sale proceeds are withdrawn with WithdrawDirectProceeds");
+            return Err(SaleModeMismatch.into());
+        }
+
+        // Checking if the sale is over, the pool liquidity backs the swaps until then
+        let current_network_time = Clock::get()?.unix_timestamp;
+
+        if !whitelist_global_state.has_ended(current_network_time) {
+            msg!("Whitelist WithdrawProceeds: Sale has not ended yet");
+            return Err(SaleNotEnded.into());
+        }
+
+        if amount == 0 {
+            msg!("Whitelist WithdrawProceeds: Amount to withdraw should be greater than 0");
+            return Err(InvalidWithdrawAmount.into());
+        }
+
+        // Checking the program id equality (token swap and token programs)
+        if !token_swap_program_account.key.eq(&token_swap_program_id()) {
+            msg!("Whitelist WithdrawProceeds: Incorrect Token Swap Program ID");
+            return Err(IncorrectSwapProgram.into());
+        }
+
+        if !token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist WithdrawProceeds: Incorrect Token Program ID");
+            return Err(IncorrectTokenProgram.into());
+        }
+
+        // Checking if the pool accounts are the ones the whitelist is bound to
+        if !token_swap_state_account
+            .key
+            .eq(&whitelist_global_state.token_swap_pool_state)
+        {
+            msg!("Whitelist WithdrawProceeds: Token Swap State Account mismatch");
+            return Err(PoolMismatch.into());
+        }
+
+        if !token_swap_quote_token_account
+            .key
+            .eq(&whitelist_global_state.quote_token_account)
+            || !token_swap_wlst_token_account
+                .key
+                .eq(&whitelist_global_state.y_token_account)
+        {
+            msg!("Whitelist WithdrawProceeds: Pool token accounts mismatch");
+            return Err(PoolMismatch.into());
+        }
+
+        // Checking if the pool tokens being burnt belong to the whitelist
+        if !whitelist_pool_token_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist WithdrawProceeds: Passed Pool Token account is not a Token Program Account");
+            return Err(NotATokenAccount.into());
+        }
+
+        let whitelist_pool_token_state =
+            TokenState::unpack(&whitelist_pool_token_account.data.borrow())?;
+
+        if !whitelist_pool_token_state
+            .owner
+            .eq(whitelist_global_state_account.key)
+        {
+            msg!("Whitelist WithdrawProceeds: Pool Token Account's owner is not the Whitelist PDA");
+            return Err(IncorrectTokenOwner.into());
+        }
+
+        if !treasury_account.key.eq(&whitelist_global_state.treasury) {
+            msg!("Whitelist WithdrawProceeds: Treasury account mismatch");
+            return Err(TreasuryMismatch.into());
+        }
+
+        // Withdrawn SOL lands in the whitelist's own native SOL account to be unwrapped,
+        // any other quote mint goes straight to the treasury's associated token account
+        let is_native_quote = whitelist_global_state.quote_mint.eq(&native_mint_account());
+        let proceeds_token_address = if is_native_quote {
+            get_associated_token_address(whitelist_global_state_account.key, &native_mint_account())
+        } else {
+            get_associated_token_address(treasury_account.key, &whitelist_global_state.quote_mint)
+        };
+
+        if !proceeds_token_account.key.eq(&proceeds_token_address) {
+            msg!("Whitelist WithdrawProceeds: Proceeds Associated Token Account mismatch");
+            return Err(AssociatedAccountMismatch.into());
+        }
+
+        // The pool expects its token accounts in its own A / B order
+        let pool_state = SwapVersion::unpack(&token_swap_state_account.data.borrow())?;
+        let (swap_token_a_account, swap_token_b_account) = if pool_state
+            .token_a_account()
+            .eq(token_swap_quote_token_account.key)
+        {
+            (
+                token_swap_quote_token_account,
+                token_swap_wlst_token_account,
+            )
+        } else {
+            (
+                token_swap_wlst_token_account,
+                token_swap_quote_token_account,
+            )
+        };
+
+        msg!("Withdrawing the proceeds from the pool");
+        let withdraw_proceeds_ix = withdraw_single_token_type_exact_amount_out(
+            &token_swap_program_id(),
+            &token_program_id(),
+            token_swap_state_account.key,
+            swap_authority_pda_account.key,
+            whitelist_global_state_account.key,
+            pool_mint_token_account.key,
+            pool_token_fee_account.key,
+            whitelist_pool_token_account.key,
+            swap_token_a_account.key,
+            swap_token_b_account.key,
+            proceeds_token_account.key,
+            WithdrawSingleTokenTypeExactAmountOut {
+                destination_token_amount: amount,
+                maximum_pool_token_amount,
+            },
+        )?;
+
+        invoke_signed_by_whitelist_pda(
+            &withdraw_proceeds_ix,
+            &[
+                token_swap_state_account.clone(),
+                swap_authority_pda_account.clone(),
+                whitelist_global_state_account.clone(),
+                pool_mint_token_account.clone(),
+                whitelist_pool_token_account.clone(),
+                swap_token_a_account.clone(),
+                swap_token_b_account.clone(),
+                proceeds_token_account.clone(),
+                pool_token_fee_account.clone(),
+                token_program_account.clone(),
+                token_swap_program_account.clone(),
+            ],
+            &whitelist_global_state,
+        )?;
+
+        if is_native_quote {
+            // Closing the whitelist's native SOL account unwraps everything in it to the treasury
+            msg!("Unwrapping the proceeds to the treasury");
+            let unwrap_proceeds_ix = close_account(
+                &token_program_id(),
+                proceeds_token_account.key,
+                treasury_account.key,
+                whitelist_global_state_account.key,
+                &[],
+            )?;
+
+            invoke_signed_by_whitelist_pda(
+                &unwrap_proceeds_ix,
+                &[
+                    proceeds_token_account.clone(),
+                    treasury_account.clone(),
+                    whitelist_global_state_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &whitelist_global_state,
+            )?;
+        }
+
+        whitelist_global_state.total_proceeds_withdrawn = whitelist_global_state
+            .total_proceeds_withdrawn
+            .checked_add(amount)
+            .ok_or(MathOverflow)?;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Withdrew {} quote tokens of proceeds to the treasury {}",
+            amount.to_string(),
+            treasury_account.key.to_string()
+        );
+
+        Ok(())
+    }
+
+    // INITIALIZE DIRECT SALE
+    #[allow(clippy::too_many_arguments)]
+    fn process_whitelist_initialize_direct_sale(
+        merkle_root: [u8; 32],
+        price_per_token_y: u64,
+        min_contribution: u64,
+        max_contribution: u64,
+        sale_start_ts: UnixTimestamp,
+        sale_end_ts: UnixTimestamp,
+        sale_id: u64,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        // All accounts
+        let accounts_iterable = &mut accounts.iter();
+        let whitelist_creator = next_account_info(accounts_iterable)?;
+        let whitelist_pda_account = next_account_info(accounts_iterable)?;
+        let y_token_mint_account = next_account_info(accounts_iterable)?;
+        let y_vault_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+        let whitelist_registry_account = next_account_info(accounts_iterable)?;
+
+        // Only the canonical bump is accepted, so a sale has a single global state address
+        let (whitelist_program_address, whitelist_pda_bump) = find_whitelist_program_address(
+            whitelist_creator.key,
+            y_vault_account.key,
+            sale_id,
+            program_id,
+        );
+
+        let pda_seeds_bump: &[&[u8]] = &[
+            WHITELIST_PDA_SEED,
+            &whitelist_creator.key.to_bytes(),
+            &y_vault_account.key.to_bytes(),
+            &sale_id.to_le_bytes(),
+            &[whitelist_pda_bump],
+        ];
+
+        check_sale_config(
+            "InitDirectSale",
+            price_per_token_y,
+            min_contribution,
+            max_contribution,
+            sale_start_ts,
+            sale_end_ts,
+        )?;
+
+        // Checking if the resultant PDA address matches
+        if !whitelist_pda_account.key.eq(&whitelist_program_address) {
+            msg!("Whitelist InitDirectSale: PDA accounts mismatch");
+            return Err(IncorrectPdaAccount.into());
+        }
+
+        // Checking if the sale creator has signed
+        if !whitelist_creator.is_signer {
+            msg!("Whitelist InitDirectSale: Sale creator has not signed");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Checking if the system program id is the one expected
+        if !system_program_account.key.eq(&system_program_id()) {
+            msg!("Whitelist InitDirectSale: System Program accounts mismatch");
+            return Err(IncorrectSystemProgram.into());
+        }
+
+        // Checking if the vault is a Y token account owned by the PDA
+        if !y_vault_account.owner.eq(&token_program_id()) {
+            msg!("Whitelist InitDirectSale: Y Token vault passed is not a Token Program Account");
+            return Err(NotATokenAccount.into());
+        }
+
+        let y_vault_state = TokenState::unpack(&y_vault_account.data.borrow())?;
+
+        if !y_vault_state.mint.eq(y_token_mint_account.key) {
+            msg!("Whitelist InitDirectSale: Y Token vault mint mismatch");
+            return Err(MintMismatch.into());
+        }
+
+        if !y_vault_state.owner.eq(&whitelist_program_address) {
+            msg!("Whitelist InitDirectSale: Y Token vault's owner is not the Whitelist PDA");
+            return Err(IncorrectTokenOwner.into());
+        }
+
+        // A delegate or close authority could drain the vault behind the PDA's back
+        if y_vault_state.delegate.is_some() || y_vault_state.close_authority.is_some() {
+            msg!("Whitelist InitDirectSale: Y Token vault has a delegate or close authority");
+            return Err(IncorrectTokenOwner.into());
+        }
+
+        // Checking if the PDA has already been initialized, which the system program would
+        // only reject with an opaque error
+        if !whitelist_pda_account.owner.eq(&system_program_id()) {
+            msg!("Whitelist InitDirectSale: PDA account has already been initialized");
+
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        create_pda_account(
+            whitelist_creator,
+            whitelist_pda_account,
+            system_program_account,
+            pda_seeds_bump,
+            get_state_packed_len::<WhitelistPDAGlobalState>(),
+            program_id,
+        )?;
+
+        let mut whitelist_pda_state =
+            WhitelistPDAGlobalState::try_from_slice(&whitelist_pda_account.data.borrow())?;
+
+        msg!(
+            "Initializing the direct sale PDA Account of address {}",
+            whitelist_program_address
+        );
+
+        whitelist_pda_state.version = GLOBAL_STATE_VERSION;
+        whitelist_pda_state.is_initialized = true;
+        whitelist_pda_state.whitelist_creator = *whitelist_creator.key;
+        whitelist_pda_state.global_pda_bump = whitelist_pda_bump;
+        whitelist_pda_state.whitelist_merkle_root = merkle_root;
+        whitelist_pda_state.y_mint_account = *y_token_mint_account.key;
+        whitelist_pda_state.y_token_account = *y_vault_account.key;
+        whitelist_pda_state.price_per_token_y = price_per_token_y;
+        whitelist_pda_state.whitelist_authority = *whitelist_creator.key;
+        whitelist_pda_state.whitelist_admin = *whitelist_creator.key;
+        whitelist_pda_state.min_contribution = min_contribution;
+        whitelist_pda_state.max_contribution = max_contribution;
+        whitelist_pda_state.sale_start_ts = sale_start_ts;
+        whitelist_pda_state.sale_end_ts = sale_end_ts;
+        whitelist_pda_state.is_fixed_price = true;
+        whitelist_pda_state.treasury = *whitelist_creator.key;
+        whitelist_pda_state.seed_y_token_account = *y_vault_account.key;
+        whitelist_pda_state.is_direct_sale = true;
+        whitelist_pda_state.sale_id = sale_id;
+        whitelist_pda_state.quote_mint = native_mint_account();
+
+        whitelist_pda_state.serialize(&mut &mut whitelist_pda_account.data.borrow_mut()[..])?;
+
+        register_whitelist_sale(
+            "InitDirectSale",
+            whitelist_creator,
+            whitelist_registry_account,
+            system_program_account,
+            whitelist_pda_account.key,
+            program_id,
+        )?;
+
+        WhiteListEvent::SaleInitialized {
+            sale: *whitelist_pda_account.key,
+            creator: *whitelist_creator.key,
+            y_mint: *y_token_mint_account.key,
+            sale_id,
+            price_per_token_y,
+            is_direct_sale: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit();
+
+        msg!("Creation of the direct sale PDA complete successfully");
+
+        Ok(())
+    }
+
+    // BUY DIRECT
+    fn process_whitelist_buy_direct(
+        input_sol_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let y_vault_account = next_account_info(accounts_iterable)?;
+        let user_wlst_token_account = next_account_info(accounts_iterable)?;
+        let token_program_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+
+        // Optional whitelist entry account, checked instead of the Merkle proof
+        let whitelist_entry_account = accounts_iterable.next();
+
+        let current_network_time = Clock::get()?.unix_timestamp;
+
+        // Checking if the user has signed
+        if !user_account.is_signer {
+            msg!("Whitelist BuyDirect: User not signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist BuyDirect: Incorrect Token Program ID");
+            return Err(IncorrectTokenProgram.into());
+        }
+
+        let mut whitelist_global_state =
+            unpack_whitelist_global_state("BuyDirect", whitelist_global_state_account, program_id)?;
+
+        if !whitelist_global_state.is_direct_sale {
+            msg!("Whitelist BuyDirect: Pool sales are bought with SwapSOLToken");
+            return Err(SaleModeMismatch.into());
+        }
+
+        // Checking if the vault is the one the sale was initialized with
+        if !y_vault_account
+            .key
+            .eq(&whitelist_global_state.y_token_account)
+        {
+            msg!("Whitelist BuyDirect: Y Token vault mismatch");
+            return Err(VaultMismatch.into());
+        }
+
+        check_user_wlst_token_account(
+            "BuyDirect",
+            user_account,
+            user_wlst_token_account,
+            whitelist_global_state_account,
+            &whitelist_global_state,
+            program_id,
+        )?;
+
+        let (mut whitelist_user_state, user_state_bump) = load_whitelist_user_state(
+            "BuyDirect",
+            user_account,
+            whitelist_user_state_account,
+            whitelist_global_state_account,
+            system_program_account,
+            program_id,
+        )?;
+
+        let (price_per_token_y, total_contributed) = check_purchase(
+            "BuyDirect",
+            input_sol_amount,
+            allocation,
+            &proof,
+            current_network_time,
+            user_account,
+            whitelist_global_state_account,
+            &whitelist_global_state,
+            &whitelist_user_state,
+            whitelist_entry_account,
+            program_id,
+        )?;
+
+        // Direct sales always sell at the fixed price
+        let spl_token_amount = input_sol_amount
+            .checked_div(price_per_token_y)
+            .ok_or(MathOverflow)?;
+
+        if spl_token_amount == 0 {
+            msg!("Whitelist BuyDirect: Purchase amount does not buy a single Y token");
+            return Err(ContributionTooSmall.into());
+        }
+
+        // The whole purchase amount is kept, so it has to pay for whole Y tokens only
+        if spl_token_amount.checked_mul(price_per_token_y) != Some(input_sol_amount) {
+            msg!("Whitelist BuyDirect: Purchase amount is not a multiple of the token price");
+            return Err(AmountNotPriceMultiple.into());
+        }
+
+        // Tokens escrowed for earlier buyers of a claim later sale are not for sale anymore
+        let y_vault_state = TokenState::unpack(&y_vault_account.data.borrow())?;
+        let y_vault_available_amount = y_vault_state
+            .amount
+            .saturating_sub(whitelist_global_state.total_y_escrowed);
+
+        if y_vault_available_amount.lt(&spl_token_amount) {
+            msg!("Whitelist BuyDirect: Y Token vault has not enough tokens left");
+            return Err(VaultDepleted.into());
+        }
+
+        // Paying the SOL to the PDA, which holds it until it is withdrawn
+        msg!("Transferring SOL to the Whitelist PDA");
+        let transfer_lamports_to_pda_ix = system_instruction::transfer(
+            user_account.key,
+            whitelist_global_state_account.key,
+            input_sol_amount,
+        );
+
+        invoke(
+            &transfer_lamports_to_pda_ix,
             &[
-                funding_account.clone(),
-                associated_token_pda_account.clone(),
+                user_account.clone(),
+                whitelist_global_state_account.clone(),
                 system_program_account.clone(),
             ],
         )?;
 
-        // Sync Nativeing the associated account to update the wSOL balance
-        msg!("Sync Nativeing the associated account to update the wSOL balance");
-        let sync_native_sol_ix =
-            sync_native(&token_program_id(), &associated_token_pda_account.key)?;
+        if whitelist_global_state.is_claim_later {
+            msg!("Escrowing the Y tokens in the vault until the sale ends");
+            whitelist_global_state.total_y_escrowed = whitelist_global_state
+                .total_y_escrowed
+                .checked_add(spl_token_amount)
+                .ok_or(MathOverflow)?;
+        } else {
+            msg!("Transferring Y tokens from the vault");
+            let transfer_y_tokens_ix = transfer(
+                &token_program_id(),
+                y_vault_account.key,
+                user_wlst_token_account.key,
+                whitelist_global_state_account.key,
+                &[],
+                spl_token_amount,
+            )?;
+
+            invoke_signed_by_whitelist_pda(
+                &transfer_y_tokens_ix,
+                &[
+                    y_vault_account.clone(),
+                    user_wlst_token_account.clone(),
+                    whitelist_global_state_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &whitelist_global_state,
+            )?;
+
+            if whitelist_global_state.is_vesting {
+                whitelist_global_state.total_y_vesting = whitelist_global_state
+                    .total_y_vesting
+                    .checked_add(spl_token_amount)
+                    .ok_or(MathOverflow)?;
+            }
+        }
+
+        msg!("Assigning state for the whitelist user");
+        if !whitelist_user_state.is_initialized() {
+            whitelist_user_state.is_initialized = true;
+            whitelist_user_state.whitelisted_by_account = *user_account.key;
+            whitelist_user_state.whitelisted_at = current_network_time;
+            whitelist_user_state.user_state_bump = user_state_bump;
+        }
+        whitelist_user_state.total_contributed = total_contributed;
+        whitelist_user_state.total_received = whitelist_user_state
+            .total_received
+            .checked_add(spl_token_amount)
+            .ok_or(MathOverflow)?;
+
+        whitelist_user_state
+            .serialize(&mut &mut whitelist_user_state_account.data.borrow_mut()[..])?;
+
+        whitelist_global_state.total_raised = whitelist_global_state
+            .total_raised
+            .checked_add(input_sol_amount)
+            .ok_or(MathOverflow)?;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        WhiteListEvent::Purchased {
+            sale: *whitelist_global_state_account.key,
+            user: *user_account.key,
+            sol_amount: input_sol_amount,
+            y_amount: spl_token_amount,
+            price_per_token_y,
+            timestamp: current_network_time,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // WITHDRAW DIRECT PROCEEDS
+    fn process_whitelist_withdraw_direct_proceeds(
+        amount: u64,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let treasury_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "WithdrawDirectProceeds",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "WithdrawDirectProceeds",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        if !whitelist_global_state.is_direct_sale {
+            msg!("Whitelist WithdrawDirectProceeds: Pool sale proceeds are withdrawn with WithdrawProceeds");
+            return Err(SaleModeMismatch.into());
+        }
+
+        if !treasury_account.key.eq(&whitelist_global_state.treasury) {
+            msg!("Whitelist WithdrawDirectProceeds: Treasury account mismatch");
+            return Err(TreasuryMismatch.into());
+        }
+
+        // Contributions stay refundable until the soft cap is reached
+        if whitelist_global_state.is_below_soft_cap() {
+            msg!("Whitelist WithdrawDirectProceeds: Sale has not reached its soft cap");
+            return Err(SoftCapNotReached.into());
+        }
+
+        // The PDA has to stay rent exempt
+        let rent_exempt_lamports =
+            Rent::get()?.minimum_balance(whitelist_global_state_account.data_len());
+        let withdrawable_lamports = whitelist_global_state_account
+            .lamports()
+            .saturating_sub(rent_exempt_lamports);
+
+        if amount == 0 || amount.gt(&withdrawable_lamports) {
+            msg!(
+                "Whitelist WithdrawDirectProceeds: Amount should be between 1 and {} lamports",
+                withdrawable_lamports.to_string()
+            );
+            return Err(InvalidWithdrawAmount.into());
+        }
+
+        // The PDA is owned by this program, so its lamports can be moved directly
+        **whitelist_global_state_account.try_borrow_mut_lamports()? -= amount;
+        **treasury_account.try_borrow_mut_lamports()? += amount;
+
+        whitelist_global_state.total_proceeds_withdrawn = whitelist_global_state
+            .total_proceeds_withdrawn
+            .checked_add(amount)
+            .ok_or(MathOverflow)?;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Withdrew {} lamports of proceeds to the treasury {}",
+            amount.to_string(),
+            treasury_account.key.to_string()
+        );
+
+        Ok(())
+    }
+
+    // SET SALE CAPS
+    fn process_whitelist_set_sale_caps(
+        soft_cap: u64,
+        hard_cap: u64,
+        is_claim_later: bool,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "SetSaleCaps",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "SetSaleCaps",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        // Checking if the sale hasn't raised anything yet, so no contribution changes its terms
+        if whitelist_global_state.total_raised > 0 {
+            msg!("Whitelist SetSaleCaps: Caps can't change once the sale has raised SOL");
+            return Err(SaleTermsLocked.into());
+        }
+
+        if hard_cap > 0 && soft_cap.gt(&hard_cap) {
+            msg!("Whitelist SetSaleCaps: Soft cap is above the hard cap");
+            return Err(InvalidSaleCaps.into());
+        }
+
+        // Only a direct sale holds the SOL and the tokens it would have to give back
+        if (soft_cap > 0 || is_claim_later) && !whitelist_global_state.is_direct_sale {
+            msg!("Whitelist SetSaleCaps: Soft caps and claim later need a direct sale");
+            return Err(SaleModeMismatch.into());
+        }
+
+        if soft_cap > 0 && !is_claim_later {
+            msg!("Whitelist SetSaleCaps: Soft caps need the tokens to be claimed later");
+            return Err(InvalidSaleCaps.into());
+        }
+
+        if is_claim_later && whitelist_global_state.sale_end_ts == 0 {
+            msg!("Whitelist SetSaleCaps: Claim later sales need an end time");
+            return Err(InvalidSaleSchedule.into());
+        }
+
+        if is_claim_later && whitelist_global_state.is_vesting {
+            msg!("Whitelist SetSaleCaps: Vesting sales can't escrow tokens to claim later");
+            return Err(InvalidVestingSchedule.into());
+        }
+
+        whitelist_global_state.soft_cap = soft_cap;
+        whitelist_global_state.hard_cap = hard_cap;
+        whitelist_global_state.is_claim_later = is_claim_later;
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist caps set to a soft cap of {} and a hard cap of {} lamports",
+            soft_cap.to_string(),
+            hard_cap.to_string()
+        );
+
+        Ok(())
+    }
+
+    // REFUND
+    fn process_whitelist_refund(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+
+        // Checking if the user has signed
+        if !user_account.is_signer {
+            msg!("Whitelist Refund: User not signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut whitelist_global_state =
+            unpack_whitelist_global_state("Refund", whitelist_global_state_account, program_id)?;
+
+        // Refunds aren't paused with the sale, pausing must never lock up the users' SOL
+
+        let mut whitelist_user_state = unpack_whitelist_user_state(
+            "Refund",
+            user_account,
+            whitelist_user_state_account,
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // Checking if the sale is over and missed its soft cap
+        let current_network_time = Clock::get()?.unix_timestamp;
+
+        if !whitelist_global_state.has_ended(current_network_time) {
+            msg!("Whitelist Refund: Sale has not ended yet");
+            return Err(SaleNotEnded.into());
+        }
+
+        if !whitelist_global_state.is_below_soft_cap() {
+            msg!("Whitelist Refund: Sale has reached its soft cap");
+            return Err(SoftCapReached.into());
+        }
+
+        let refund_amount = whitelist_user_state
+            .total_contributed
+            .saturating_sub(whitelist_user_state.total_refunded);
+
+        if refund_amount == 0 {
+            msg!("Whitelist Refund: User has nothing left to be refunded");
+            return Err(NothingToClaim.into());
+        }
+
+        // The PDA is owned by this program, so its lamports can be moved directly
+        msg!(
+            "Refunding {} lamports to the user",
+            refund_amount.to_string()
+        );
+        let whitelist_global_state_lamports = whitelist_global_state_account
+            .lamports()
+            .checked_sub(refund_amount)
+            .ok_or(InsufficientSol)?;
+        let user_lamports = user_account
+            .lamports()
+            .checked_add(refund_amount)
+            .ok_or(MathOverflow)?;
+
+        **whitelist_global_state_account.try_borrow_mut_lamports()? =
+            whitelist_global_state_lamports;
+        **user_account.try_borrow_mut_lamports()? = user_lamports;
+
+        whitelist_user_state.total_refunded = whitelist_user_state.total_contributed;
+        whitelist_global_state.total_refunded = whitelist_global_state
+            .total_refunded
+            .checked_add(refund_amount)
+            .ok_or(MathOverflow)?;
+
+        whitelist_user_state
+            .serialize(&mut &mut whitelist_user_state_account.data.borrow_mut()[..])?;
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        WhiteListEvent::Refunded {
+            sale: *whitelist_global_state_account.key,
+            user: *user_account.key,
+            sol_amount: refund_amount,
+            timestamp: current_network_time,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // CLAIM TOKENS
+    fn process_whitelist_claim_tokens(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let y_vault_account = next_account_info(accounts_iterable)?;
+        let user_wlst_token_account = next_account_info(accounts_iterable)?;
+        let token_program_account = next_account_info(accounts_iterable)?;
+
+        // Checking if the user has signed
+        if !user_account.is_signer {
+            msg!("Whitelist ClaimTokens: User not signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist ClaimTokens: Incorrect Token Program ID");
+            return Err(IncorrectTokenProgram.into());
+        }
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "ClaimTokens",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // Claims aren't paused with the sale, pausing must never lock up the users' tokens
+
+        if !whitelist_global_state.is_claim_later {
+            msg!("Whitelist ClaimTokens: Tokens of this sale are not escrowed");
+            return Err(SaleModeMismatch.into());
+        }
+
+        let mut whitelist_user_state = unpack_whitelist_user_state(
+            "ClaimTokens",
+            user_account,
+            whitelist_user_state_account,
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // Checking if the sale is over and reached its soft cap
+        let current_network_time = Clock::get()?.unix_timestamp;
+
+        if !whitelist_global_state.has_ended(current_network_time) {
+            msg!("Whitelist ClaimTokens: Sale has not ended yet");
+            return Err(SaleNotEnded.into());
+        }
+
+        if whitelist_global_state.is_below_soft_cap() {
+            msg!("Whitelist ClaimTokens: Sale has not reached its soft cap");
+            return Err(SoftCapNotReached.into());
+        }
+
+        if !y_vault_account
+            .key
+            .eq(&whitelist_global_state.y_token_account)
+        {
+            msg!("Whitelist ClaimTokens: Y Token vault mismatch");
+            return Err(VaultMismatch.into());
+        }
+
+        let claim_amount = whitelist_user_state
+            .total_received
+            .saturating_sub(whitelist_user_state.total_claimed);
+
+        if claim_amount == 0 {
+            msg!("Whitelist ClaimTokens: User has nothing left to claim");
+            return Err(NothingToClaim.into());
+        }
+
+        // The token program checks the destination's mint against the vault's
+        msg!("Transferring the escrowed Y tokens from the vault");
+        let transfer_y_tokens_ix = transfer(
+            &token_program_id(),
+            y_vault_account.key,
+            user_wlst_token_account.key,
+            whitelist_global_state_account.key,
+            &[],
+            claim_amount,
+        )?;
+
+        invoke_signed_by_whitelist_pda(
+            &transfer_y_tokens_ix,
+            &[
+                y_vault_account.clone(),
+                user_wlst_token_account.clone(),
+                whitelist_global_state_account.clone(),
+                token_program_account.clone(),
+            ],
+            &whitelist_global_state,
+        )?;
+
+        whitelist_user_state.total_claimed = whitelist_user_state.total_received;
+        whitelist_global_state.total_y_escrowed = whitelist_global_state
+            .total_y_escrowed
+            .saturating_sub(claim_amount);
+
+        whitelist_user_state
+            .serialize(&mut &mut whitelist_user_state_account.data.borrow_mut()[..])?;
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        WhiteListEvent::Claimed {
+            sale: *whitelist_global_state_account.key,
+            user: *user_account.key,
+            y_amount: claim_amount,
+            timestamp: current_network_time,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // SET VESTING SCHEDULE
+    fn process_whitelist_set_vesting_schedule(
+        is_vesting: bool,
+        vesting_cliff_ts: UnixTimestamp,
+        vesting_duration: i64,
+        vesting_tge_unlock_bps: u16,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "SetVestingSchedule",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        check_whitelist_authority(
+            "SetVestingSchedule",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
+
+        // Checking if the sale hasn't raised anything yet, so no purchase changes its terms
+        if whitelist_global_state.total_raised > 0 {
+            msg!("Whitelist SetVestingSchedule: Vesting can't change once the sale has raised SOL");
+            return Err(SaleTermsLocked.into());
+        }
 
-        invoke(
-            &sync_native_sol_ix,
-            &[
-                associated_token_pda_account.clone(),
-                spl_token_program_account.clone(),
-            ],
-        )?;
+        if is_vesting {
+            if vesting_duration < 0 || vesting_tge_unlock_bps > MAX_BASIS_POINTS {
+                msg!(
+                    "Whitelist SetVestingSchedule: Duration should not be negative and the TGE unlock at most {} bps",
+                    MAX_BASIS_POINTS
+                );
+                return Err(InvalidVestingSchedule.into());
+            }
+
+            if whitelist_global_state.is_claim_later {
+                msg!("Whitelist SetVestingSchedule: Vesting sales can't escrow tokens to claim later");
+                return Err(InvalidVestingSchedule.into());
+            }
+        }
+
+        whitelist_global_state.is_vesting = is_vesting;
+        whitelist_global_state.vesting_cliff_ts = if is_vesting { vesting_cliff_ts } else { 0 };
+        whitelist_global_state.vesting_duration = if is_vesting { vesting_duration } else { 0 };
+        whitelist_global_state.vesting_tge_unlock_bps = if is_vesting {
+            vesting_tge_unlock_bps
+        } else {
+            0
+        };
+
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        msg!(
+            "Whitelist vesting set to a cliff at {} and a duration of {} seconds",
+            whitelist_global_state.vesting_cliff_ts.to_string(),
+            whitelist_global_state.vesting_duration.to_string()
+        );
 
         Ok(())
     }
 
-    // CREATE AND WRAP SOL
-    fn process_whitelist_create_and_wrap_sol(
-        amount_to_be_wrapped: u64,
+    // CREATE VESTING VAULT
+    fn process_whitelist_create_vesting_vault(
         accounts: &[AccountInfo],
+        program_id: &Pubkey,
     ) -> ProgramResult {
         let accounts_iterable = &mut accounts.iter();
         // All accounts
-        let funding_account = next_account_info(accounts_iterable)?;
-        let associated_token_pda_account = next_account_info(accounts_iterable)?;
-        let native_token_mint_account = next_account_info(accounts_iterable)?;
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let vesting_vault_account = next_account_info(accounts_iterable)?;
+        let y_token_mint_account = next_account_info(accounts_iterable)?;
+        let token_program_account = next_account_info(accounts_iterable)?;
         let system_program_account = next_account_info(accounts_iterable)?;
-        let spl_token_program_account = next_account_info(accounts_iterable)?;
-        let associated_token_program_account = next_account_info(accounts_iterable)?;
         let rent_sysvar_account = next_account_info(accounts_iterable)?;
 
-        let associated_account_address =
-            get_associated_token_address(&funding_account.key, &native_mint_account());
+        // Checking if the user has signed
+        if !user_account.is_signer {
+            msg!("Whitelist CreateVestingVault: User not signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
 
-        // Processes a bunch of checks which are common for both create-wrap and wrap SOL
+        if !token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist CreateVestingVault: Incorrect Token Program ID");
+            return Err(IncorrectTokenProgram.into());
+        }
 
-        check_for_wrapping(
-            "CreateAndWrapSOLTokenToken",
-            amount_to_be_wrapped,
-            &associated_account_address,
-            funding_account,
-            associated_token_pda_account,
-            spl_token_program_account,
-            system_program_account,
+        if !system_program_account.key.eq(&system_program_id()) {
+            msg!("Whitelist CreateVestingVault: System Program accounts mismatch");
+            return Err(IncorrectSystemProgram.into());
+        }
+
+        let whitelist_global_state = unpack_whitelist_global_state(
+            "CreateVestingVault",
+            whitelist_global_state_account,
+            program_id,
         )?;
 
-        // Checking if the right spl associated token program account has been passed
-        if !associated_token_program_account
+        if !whitelist_global_state.is_vesting {
+            msg!("Whitelist CreateVestingVault: Sale has no vesting schedule");
+            return Err(SaleModeMismatch.into());
+        }
+
+        if !y_token_mint_account
             .key
-            .eq(&associated_token_program_id())
+            .eq(&whitelist_global_state.y_mint_account)
         {
-            msg!("WhiteList CreateAndWrapSOLToken: Passed Associated Token Program key mismatches with original Token Program key");
-            return Err(ProgramError::IncorrectProgramId);
+            msg!("Whitelist CreateVestingVault: Y Token Mint mismatch");
+            return Err(MintMismatch.into());
         }
 
-        // Checking if the corrent native mint account has been passed
-        if !native_token_mint_account.key.eq(&native_mint_account()) {
-            msg!("WhiteList CreateAndWrapSOLToken: Passed Native Mint account mismatch with original native mint account");
-            return Err(ProgramError::IncorrectProgramId);
+        // Checking if the vault account is the PDA derived for this user
+        let (vesting_vault_address, vesting_vault_bump) =
+            find_whitelist_vesting_vault_program_address(
+                whitelist_global_state_account.key,
+                user_account.key,
+                program_id,
+            );
+
+        if !vesting_vault_account.key.eq(&vesting_vault_address) {
+            msg!("Whitelist CreateVestingVault: Vesting vault PDA account mismatch");
+            return Err(IncorrectStateAccount.into());
         }
 
-        // Checking if the pda account is not initialized
-        if !associated_token_pda_account.owner.eq(&system_program_id()) {
-            msg!("WhiteList CreateAndWrapSOLToken: Associated account already exists");
+        if !vesting_vault_account.owner.eq(&system_program_id()) {
+            msg!("Whitelist CreateVestingVault: Vesting vault has already been created");
             return Err(ProgramError::AccountAlreadyInitialized);
         }
 
-        // Business logic starts
+        let vesting_vault_seeds_bump: &[&[u8]] = &[
+            WHITELIST_VESTING_SEED,
+            &whitelist_global_state_account.key.to_bytes(),
+            &user_account.key.to_bytes(),
+            &[vesting_vault_bump],
+        ];
 
-        // Transferring the required lamports to be wrapped to the PDA
-        msg!("Transferring SOL to Associated account");
-        let transfer_lamports_to_assoc_account_ix = system_instruction::transfer(
-            &funding_account.key,
-            &associated_account_address,
-            amount_to_be_wrapped,
-        );
+        // The vault is a token account, so the token program owns it
+        create_pda_account(
+            user_account,
+            vesting_vault_account,
+            system_program_account,
+            vesting_vault_seeds_bump,
+            TokenState::LEN,
+            &token_program_id(),
+        )?;
+
+        // CPI -> Initializing the vault as a Y token account owned by the Whitelist PDA
+        msg!("Initializing the vesting vault as a Y token account");
+        let initialize_vesting_vault_ix = initialize_account(
+            &token_program_id(),
+            vesting_vault_account.key,
+            y_token_mint_account.key,
+            whitelist_global_state_account.key,
+        )?;
 
         invoke(
-            &transfer_lamports_to_assoc_account_ix,
+            &initialize_vesting_vault_ix,
             &[
-                funding_account.clone(),
-                associated_token_pda_account.clone(),
-                system_program_account.clone(),
+                vesting_vault_account.clone(),
+                y_token_mint_account.clone(),
+                whitelist_global_state_account.clone(),
+                rent_sysvar_account.clone(),
+                token_program_account.clone(),
             ],
         )?;
 
-        // Creating a Associated token account for the funded account
-        msg!("Creating an Associated Token Account for the funded account");
-        let create_associated_token_for_funded_account_ix = create_associated_token_account(
-            &funding_account.key,
-            &funding_account.key,
-            &native_mint_account(),
+        Ok(())
+    }
+
+    // CLAIM VESTED
+    fn process_whitelist_claim_vested(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let vesting_vault_account = next_account_info(accounts_iterable)?;
+        let user_wlst_token_account = next_account_info(accounts_iterable)?;
+        let token_program_account = next_account_info(accounts_iterable)?;
+
+        // Checking if the user has signed
+        if !user_account.is_signer {
+            msg!("Whitelist ClaimVested: User not signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist ClaimVested: Incorrect Token Program ID");
+            return Err(IncorrectTokenProgram.into());
+        }
+
+        let mut whitelist_global_state = unpack_whitelist_global_state(
+            "ClaimVested",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // Claims aren't paused with the sale, pausing must never lock up the users' tokens
+
+        if !whitelist_global_state.is_vesting {
+            msg!("Whitelist ClaimVested: Tokens of this sale are not vested");
+            return Err(SaleModeMismatch.into());
+        }
+
+        let mut whitelist_user_state = unpack_whitelist_user_state(
+            "ClaimVested",
+            user_account,
+            whitelist_user_state_account,
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        let (vesting_vault_address, _) = find_whitelist_vesting_vault_program_address(
+            whitelist_global_state_account.key,
+            user_account.key,
+            program_id,
         );
 
-        invoke(
-            &create_associated_token_for_funded_account_ix,
+        if !vesting_vault_account.key.eq(&vesting_vault_address) {
+            msg!("Whitelist ClaimVested: Vesting vault PDA account mismatch");
+            return Err(IncorrectStateAccount.into());
+        }
+
+        // Releasing whatever unlocked since the last claim
+        let current_network_time = Clock::get()?.unix_timestamp;
+        let unlocked_amount = whitelist_global_state
+            .vested_amount(whitelist_user_state.total_received, current_network_time);
+        let claim_amount = unlocked_amount.saturating_sub(whitelist_user_state.total_claimed);
+
+        if claim_amount == 0 {
+            msg!("Whitelist ClaimVested: User has nothing unlocked left to claim");
+            return Err(NothingToClaim.into());
+        }
+
+        // The token program checks the destination's mint against the vault's
+        msg!(
+            "Transferring {} unlocked Y tokens from the vesting vault",
+            claim_amount.to_string()
+        );
+        let transfer_y_tokens_ix = transfer(
+            &token_program_id(),
+            vesting_vault_account.key,
+            user_wlst_token_account.key,
+            whitelist_global_state_account.key,
+            &[],
+            claim_amount,
+        )?;
+
+        invoke_signed_by_whitelist_pda(
+            &transfer_y_tokens_ix,
             &[
-                funding_account.clone(),
-                associated_token_pda_account.clone(),
-                funding_account.clone(),
-                native_token_mint_account.clone(),
-                system_program_account.clone(),
-                spl_token_program_account.clone(),
-                rent_sysvar_account.clone(),
-                associated_token_program_account.clone(),
+                vesting_vault_account.clone(),
+                user_wlst_token_account.clone(),
+                whitelist_global_state_account.clone(),
+                token_program_account.clone(),
             ],
+            &whitelist_global_state,
         )?;
 
-        msg!("DONE :)");
+        whitelist_user_state.total_claimed = unlocked_amount;
+        whitelist_global_state.total_y_vesting = whitelist_global_state
+            .total_y_vesting
+            .saturating_sub(claim_amount);
+
+        whitelist_user_state
+            .serialize(&mut &mut whitelist_user_state_account.data.borrow_mut()[..])?;
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
+
+        WhiteListEvent::Claimed {
+            sale: *whitelist_global_state_account.key,
+            user: *user_account.key,
+            y_amount: claim_amount,
+            timestamp: current_network_time,
+        }
+        .emit();
 
         Ok(())
     }
 
-    // UNWRAP SOL
-    fn process_whitelist_unwrap_sol(accounts: &[AccountInfo]) -> ProgramResult {
+    // BUY WITH SOL
+    fn process_whitelist_buy_with_sol(
+        input_sol_amount: u64,
+        expected_spl_token_amount: u64,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
         let accounts_iterable = &mut accounts.iter();
-        // All accounts
-        let owner_account = next_account_info(accounts_iterable)?;
-        let native_assoc_pda_account = next_account_info(accounts_iterable)?;
-        let spl_token_program_account = next_account_info(accounts_iterable)?;
+        // Whitelist program related accounts
+        let user_account = next_account_info(accounts_iterable)?;
+        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
 
-        let associated_account_address =
-            get_associated_token_address(&owner_account.key, &native_mint_account());
+        // Token swap related accounts
+        let token_swap_state_account = next_account_info(accounts_iterable)?;
+        let swap_authority_pda_account = next_account_info(accounts_iterable)?;
+        let user_native_sol_token_account = next_account_info(accounts_iterable)?;
+        let user_wlst_token_account = next_account_info(accounts_iterable)?;
+        let y_token_mint_account = next_account_info(accounts_iterable)?;
+        let token_swap_native_sol_token_account = next_account_info(accounts_iterable)?;
+        let token_swap_wlst_token_account = next_account_info(accounts_iterable)?;
+        let pool_mint_token_account = next_account_info(accounts_iterable)?;
+        let pool_token_fee_account = next_account_info(accounts_iterable)?;
+        let pool_owner_account = next_account_info(accounts_iterable)?;
 
-        // Checking if the owner account has signed
-        if !owner_account.is_signer {
-            msg!("Whitelist UnwrapSOLToken: Owner account has not signed");
-            return Err(ProgramError::MissingRequiredSignature);
+        // Program IDs and sysvars
+        let token_program_account = next_account_info(accounts_iterable)?;
+        let token_swap_program_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
+        let associated_token_program_account = next_account_info(accounts_iterable)?;
+        let rent_sysvar_account = next_account_info(accounts_iterable)?;
+        let native_token_mint_account = next_account_info(accounts_iterable)?;
+
+        // Optional whitelist entry account, checked instead of the Merkle proof
+        let whitelist_entry_account = accounts_iterable.next();
+
+        if !associated_token_program_account
+            .key
+            .eq(&associated_token_program_id())
+        {
+            msg!("Whitelist BuyWithSOL: Incorrect Associated Token Program ID");
+            return Err(IncorrectAssociatedTokenProgram.into());
         }
 
-        // Checking if the spl program matches
-        if !spl_token_program_account.key.eq(&token_program_id()) {
-            msg!("Whitelist UnwrapSOLToken: SPL Program Id mismatch with original program id");
-            return Err(ProgramError::IncorrectProgramId);
+        if !native_token_mint_account.key.eq(&native_mint_account()) {
+            msg!("Whitelist BuyWithSOL: Passed Native Mint account mismatch");
+            return Err(MintMismatch.into());
         }
 
-        // Checking if the token account is a "token" account
-        if !native_assoc_pda_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist UnwrapSOLToken: Passed account is not a token account");
-            return Err(ProgramError::IllegalOwner);
+        let whitelist_global_state = unpack_whitelist_global_state(
+            "BuyWithSOL",
+            whitelist_global_state_account,
+            program_id,
+        )?;
+
+        // Only a sale paid in wSOL can be bought with plain lamports
+        if !whitelist_global_state.quote_mint.eq(&native_mint_account()) {
+            msg!("Whitelist BuyWithSOL: Sale is not quoted in native SOL");
+            return Err(SaleModeMismatch.into());
         }
 
-        // Checking if the token account is an associated account
-        if !native_assoc_pda_account.key.eq(&associated_account_address) {
-            msg!("Whitelist UnwrapSOLToken: Passed token account is not an associated account");
-            return Err(ProgramError::InvalidAccountData);
+        if !y_token_mint_account
+            .key
+            .eq(&whitelist_global_state.y_mint_account)
+        {
+            msg!("Whitelist BuyWithSOL: Y Token Mint mismatch");
+            return Err(MintMismatch.into());
         }
 
-        // Checking if the associated token account had any balance
-        if native_assoc_pda_account.lamports().eq(&0u64) {
-            msg!("Whitelist UnwrapSOLToken: Associated Token account contains 0 SOL to unwrap");
-            return Err(ProgramError::InsufficientFunds);
+        // Creating the associated accounts the user is missing, the wSOL one to wrap into
+        // and, unless the tokens vest, the Y token one to swap into
+        let mut associated_accounts =
+            vec![(user_native_sol_token_account, native_token_mint_account)];
+
+        if !whitelist_global_state.is_vesting {
+            let y_associated_account_address =
+                get_associated_token_address(user_account.key, y_token_mint_account.key);
+
+            if !user_wlst_token_account
+                .key
+                .eq(&y_associated_account_address)
+            {
+                msg!("Whitelist BuyWithSOL: Y Token Account is not the user's associated account");
+                return Err(AssociatedAccountMismatch.into());
+            }
+
+            associated_accounts.push((user_wlst_token_account, y_token_mint_account));
         }
 
-        // Business logic starts
+        associated_accounts.retain(|(associated_token_account, _)| {
+            associated_token_account.owner.eq(&system_program_id())
+        });
+        let creates_native_sol_account =
+            associated_accounts
+                .iter()
+                .any(|(associated_token_account, _)| {
+                    associated_token_account
+                        .key
+                        .eq(user_native_sol_token_account.key)
+                });
 
-        // Closing the entire associated account such that we can return back the SOL balance to the funded account
-        msg!("Closing the associated account");
-        let associated_account_close_ix = close_account(
-            &token_program_id(),
-            &native_assoc_pda_account.key,
-            &owner_account.key,
-            &owner_account.key,
-            &[&owner_account.key],
+        // The user pays the rent of the associated accounts created on top of the SOL wrapped
+        let native_associated_account_address =
+            get_associated_token_address(user_account.key, &native_mint_account());
+        let rent_storage_fees = Rent::get()?
+            .minimum_balance(TokenState::LEN)
+            .checked_mul(associated_accounts.len() as u64)
+            .ok_or(MathOverflow)?;
+
+        check_for_wrapping(
+            "BuyWithSOL",
+            input_sol_amount,
+            rent_storage_fees,
+            &native_associated_account_address,
+            user_account,
+            user_native_sol_token_account,
+            token_program_account,
+            system_program_account,
         )?;
 
+        for (associated_token_account, token_mint_account) in associated_accounts {
+            msg!(
+                "Creating the associated token account {}",
+                associated_token_account.key.to_string()
+            );
+            let create_associated_token_account_ix = create_associated_token_account_idempotent(
+                user_account.key,
+                user_account.key,
+                token_mint_account.key,
+                &token_program_id(),
+            );
+
+            invoke(
+                &create_associated_token_account_ix,
+                &[
+                    user_account.clone(),
+                    associated_token_account.clone(),
+                    user_account.clone(),
+                    token_mint_account.clone(),
+                    system_program_account.clone(),
+                    token_program_account.clone(),
+                    rent_sysvar_account.clone(),
+                    associated_token_program_account.clone(),
+                ],
+            )?;
+        }
+
+        // Wrapping the SOL to be swapped
+        msg!("Transferring SOL to the native SOL associated account");
+        let transfer_lamports_to_assoc_account_ix = system_instruction::transfer(
+            user_account.key,
+            user_native_sol_token_account.key,
+            input_sol_amount,
+        );
+
         invoke(
-            &associated_account_close_ix,
+            &transfer_lamports_to_assoc_account_ix,
             &[
-                native_assoc_pda_account.clone(),
-                owner_account.clone(),
-                owner_account.clone(),
+                user_account.clone(),
+                user_native_sol_token_account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+
+        let sync_native_sol_ix =
+            sync_native(&token_program_id(), user_native_sol_token_account.key)?;
+
+        invoke(
+            &sync_native_sol_ix,
+            &[
+                user_native_sol_token_account.clone(),
+                token_program_account.clone(),
             ],
         )?;
 
-        msg!("Done :)");
+        // Swapping with the user as its own transfer authority, through the regular swap checks
+        let mut swap_accounts = vec![
+            user_account.clone(),
+            whitelist_user_state_account.clone(),
+            whitelist_global_state_account.clone(),
+            token_swap_state_account.clone(),
+            swap_authority_pda_account.clone(),
+            user_account.clone(),
+            user_native_sol_token_account.clone(),
+            user_wlst_token_account.clone(),
+            token_swap_native_sol_token_account.clone(),
+            token_swap_wlst_token_account.clone(),
+            pool_mint_token_account.clone(),
+            pool_token_fee_account.clone(),
+            pool_owner_account.clone(),
+            token_program_account.clone(),
+            token_swap_program_account.clone(),
+            system_program_account.clone(),
+        ];
+        if let Some(whitelist_entry_account) = whitelist_entry_account {
+            swap_accounts.push(whitelist_entry_account.clone());
+        }
+
+        Self::process_whitelist_swap_sol(
+            input_sol_amount,
+            expected_spl_token_amount,
+            allocation,
+            proof,
+            &swap_accounts,
+            program_id,
+        )?;
+
+        // Closing the wSOL account created above, which unwraps whatever is left back to the
+        // user. A wSOL account the user already had is left as it was found.
+        if creates_native_sol_account {
+            msg!("Closing the native SOL associated account");
+            let close_native_sol_account_ix = close_account(
+                &token_program_id(),
+                user_native_sol_token_account.key,
+                user_account.key,
+                user_account.key,
+                &[],
+            )?;
+
+            invoke(
+                &close_native_sol_account_ix,
+                &[
+                    user_native_sol_token_account.clone(),
+                    user_account.clone(),
+                    user_account.clone(),
+                    token_program_account.clone(),
+                ],
+            )?;
+        }
 
         Ok(())
     }
 
-    // SWAP SOL FOR SPL TOKEN
-    fn process_whitelist_swap_sol(
-        input_sol_amount: u64,
-        expected_spl_token_amount: u64,
+    // MIGRATE GLOBAL STATE
+    fn process_whitelist_migrate_global_state(
         accounts: &[AccountInfo],
         program_id: &Pubkey,
     ) -> ProgramResult {
         let accounts_iterable = &mut accounts.iter();
-        // Whitelist program related accounts
-        let user_account = next_account_info(accounts_iterable)?;
-        let whitelist_user_state_account = next_account_info(accounts_iterable)?;
+        // All accounts
+        let funding_account = next_account_info(accounts_iterable)?;
         let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let system_program_account = next_account_info(accounts_iterable)?;
 
-        // Token swap related accounts
-        let token_swap_state_account = next_account_info(accounts_iterable)?;
-        let swap_authority_pda_account = next_account_info(accounts_iterable)?;
-        let user_temporary_auth_token_account = next_account_info(accounts_iterable)?;
-        let user_native_sol_token_account = next_account_info(accounts_iterable)?;
-        let user_wlst_token_account = next_account_info(accounts_iterable)?;
-        let token_swap_native_sol_token_account = next_account_info(accounts_iterable)?;
-        let token_swap_wlst_token_account = next_account_info(accounts_iterable)?;
-        let pool_mint_token_account = next_account_info(accounts_iterable)?;
-        let pool_token_fee_account = next_account_info(accounts_iterable)?;
-        let pool_owner_account = next_account_info(accounts_iterable)?;
-
-        // Program IDs
-        let token_program_account = next_account_info(accounts_iterable)?;
-        let token_swap_program_account = next_account_info(accounts_iterable)?;
-
-        let current_network_time = Clock::get()?.unix_timestamp;
-
-        // Checking if the user has signed
-        if !user_account.is_signer {
-            msg!("Whitelist SwapSOL: User not signer");
+        // Checking if the funding account has signed
+        if !funding_account.is_signer {
+            msg!("Whitelist MigrateGlobalState: Funding account has not signed");
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Checking the program id equality (token swap and token programs)
-        if !token_swap_program_account.key.eq(&token_swap_program_id()) {
-            msg!("Whitelist SwapSOL: Incorrect Token Swap Program ID");
-            return Err(ProgramError::IncorrectProgramId);
-        }
-
-        if !token_program_account.key.eq(&token_program_id()) {
-            msg!("Whitelist SwapSOL: Incorrect Token Program ID");
-            return Err(ProgramError::IncorrectProgramId);
+        if !system_program_account.key.eq(&system_program_id()) {
+            msg!("Whitelist MigrateGlobalState: System Program accounts mismatch");
+            return Err(IncorrectSystemProgram.into());
         }
 
-        // Checking if the whitelist accouts are all part of this program
         if !whitelist_global_state_account.owner.eq(program_id) {
-            msg!("Whitelist SwapSOL: Passed Whitelist global state aaccount is invalid");
+            msg!("Whitelist MigrateGlobalState: Passed Whitelist global state account is invalid");
             return Err(IncorrectStateAccount.into());
         }
 
-        if !whitelist_user_state_account.owner.eq(program_id) {
-            msg!("Whitelist SwapSOL: Passed Whitelist user state aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
-        }
-
-        // Checking the same as above, but for token and token swap accounts
-        if !token_swap_state_account.owner.eq(&token_swap_program_id()) {
-            msg!("Whitelist SwapSOL: Passed TokenSwap state aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
+        if whitelist_global_state_account.data_len() != GLOBAL_STATE_V1_LEN {
+            msg!("Whitelist MigrateGlobalState: Global state is not a version 1 account");
+            return Err(InvalidStateVersion.into());
         }
 
-        if !user_native_sol_token_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist SwapSOL: Passed Native SOL Token aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
-        }
+        let whitelist_global_state =
+            WhitelistPDAGlobalState::unpack_v1(&whitelist_global_state_account.data.borrow())?;
 
-        if !user_wlst_token_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist SwapSOL: Passed Token Y aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
+        if !whitelist_global_state.is_initialized() {
+            msg!("Whitelist MigrateGlobalState: Global state not initialized yet");
+            return Err(ProgramError::UninitializedAccount);
         }
 
-        if !token_swap_native_sol_token_account
-            .owner
-            .eq(&token_program_id())
-        {
-            msg!("Whitelist SwapSOL: Passed TokenSwap Native SOL Token aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
-        }
+        check_whitelist_global_address(
+            "MigrateGlobalState",
+            whitelist_global_state_account,
+            &whitelist_global_state,
+            program_id,
+        )?;
 
-        if !token_swap_wlst_token_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist SwapSOL: Passed TokenSwap Token Y aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
-        }
+        // Topping up the rent for the bytes the current layout adds
+        let global_state_account_span = get_state_packed_len::<WhitelistPDAGlobalState>();
+        let lamports_required = Rent::get()?
+            .minimum_balance(global_state_account_span)
+            .saturating_sub(whitelist_global_state_account.lamports());
 
-        if !pool_mint_token_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist SwapSOL: Passed TokenSwap pool mint aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
-        }
+        if lamports_required > 0 {
+            msg!("Transferring lamports to keep the migrated global state rent exempt");
+            let transfer_lamports_ix = system_instruction::transfer(
+                funding_account.key,
+                whitelist_global_state_account.key,
+                lamports_required,
+            );
 
-        if !pool_token_fee_account.owner.eq(&token_program_id()) {
-            msg!("Whitelist SwapSOL: Passed TokenSwap Pool Fee Token aaccount is invalid");
-            return Err(IncorrectStateAccount.into());
+            invoke(
+                &transfer_lamports_ix,
+                &[
+                    funding_account.clone(),
+                    whitelist_global_state_account.clone(),
+                    system_program_account.clone(),
+                ],
+            )?;
         }
 
-        // All account states
-        let whitelist_global_state =
-            WhitelistPDAGlobalState::try_from_slice(&whitelist_global_state_account.data.borrow())?;
-
-        let mut whitelist_user_state =
-            WhitelistUserState::try_from_slice(&whitelist_user_state_account.data.borrow())?;
-
-        let user_native_token_state =
-            TokenState::unpack(&user_native_sol_token_account.data.borrow())?;
-
-        let user_wlst_token_state = TokenState::unpack(&user_wlst_token_account.data.borrow())?;
+        whitelist_global_state_account.realloc(global_state_account_span, true)?;
 
-        // Checking if both NativeSOL and Y token accounts' owner is the user
-        if !user_native_token_state.owner.eq(&user_account.key) {
-            msg!("Whitelist SwapSOL: Native Token's owner is not the passed user");
+        whitelist_global_state
+            .serialize(&mut &mut whitelist_global_state_account.data.borrow_mut()[..])?;
 
-            return Err(ProgramError::IllegalOwner);
-        }
+        msg!(
+            "Global state {} migrated to layout version {}",
+            whitelist_global_state_account.key.to_string(),
+            GLOBAL_STATE_VERSION
+        );
 
-        if !user_wlst_token_state.owner.eq(&user_account.key) {
-            msg!("Whitelist SwapSOL: Native Token's owner is not the passed user");
+        Ok(())
+    }
 
-            return Err(ProgramError::IllegalOwner);
-        }
+    // WITHDRAW UNSOLD TOKENS
+    fn process_whitelist_withdraw_unsold_tokens(
+        amount: u64,
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let accounts_iterable = &mut accounts.iter();
+        // All accounts
+        let whitelist_authority = next_account_info(accounts_iterable)?;
+        let whitelist_global_state_account = next_account_info(accounts_iterable)?;
+        let y_vault_account = next_account_info(accounts_iterable)?;
+        let destination_token_account = next_account_info(accounts_iterable)?;
+        let token_program_account = next_account_info(accounts_iterable)?;
 
-        // Checking if the Native SOL Token account has the right mint
-        if !user_native_token_state.mint.eq(&native_mint_account()) {
-            msg!("Whitelist SwapSOL: Passed token account's mint is not native_sol");
-            return Err(ProgramError::InvalidAccountData);
+        if !token_program_account.key.eq(&token_program_id()) {
+            msg!("Whitelist WithdrawUnsoldTokens: Incorrect Token Program ID");
+            return Err(IncorrectTokenProgram.into());
         }
 
-        // Checking if the Y Token account has the right mint
-        if !user_wlst_token_state
-            .mint
-            .eq(&whitelist_global_state.y_mint_account)
-        {
-            msg!("Whitelist SwapSOL: User Token Y Mint mismatch");
-            return Err(ProgramError::InvalidAccountData);
-        }
+        let whitelist_global_state = unpack_whitelist_global_state(
+            "WithdrawUnsoldTokens",
+            whitelist_global_state_account,
+            program_id,
+        )?;
 
-        // Checking if the Whitelist global state account has been initialized
-        if !whitelist_global_state.is_initialized() {
-            msg!("Whitelist SwapSOL: Global state not initialized yet");
-            return Err(ProgramError::UninitializedAccount);
-        }
+        check_whitelist_authority(
+            "WithdrawUnsoldTokens",
+            whitelist_authority,
+            &whitelist_global_state,
+            accounts_iterable.as_slice(),
+        )?;
 
-        // Checking if the Whitelist User state is not initialized
-        if whitelist_user_state.is_initialized() {
-            msg!("Whitelist SwapSOL: User State already initialized");
-            return Err(ProgramError::AccountAlreadyInitialized);
+        if !whitelist_global_state.is_direct_sale {
+            msg!("Whitelist WithdrawUnsoldTokens: Only direct sales hold a Y Token vault");
+            return Err(SaleModeMismatch.into());
         }
 
-        let is_allowed = whitelist_global_state
-            .whitelist_auth_addresses
-            .iter()
-            .find(|&&address| address.eq(user_account.key))
-            .is_some();
-
-        // Checking if the user account is whitelisted
-        if !is_allowed {
-            msg!("Whitelist SwapSOL: Passed user account is not allowed for swapping");
-            return Err(AccountNotWhitelisted.into());
-        }
+        // Checking if the sale is over, nothing can be bought anymore
+        let current_network_time = Clock::get()?.unix_timestamp;
 
-        // Checking if the user's native sol token account has enough balance
-        if user_native_token_state
-            .amount
-            .lt(&whitelist_global_state.price_per_token_y)
-        {
-            msg!("Whitelist SwapSOL: Insufficient SOL recognized");
-            return Err(ProgramError::InsufficientFunds);
+        if !whitelist_global_state.has_ended(current_network_time) {
+            msg!("Whitelist WithdrawUnsoldTokens: Sale has not ended yet");
+            return Err(SaleNotEnded.into());
         }
 
-        // Checking if the Whitelist Global state's token swap state account matches with the passed account
-
-        if !token_swap_state_account
+        if !y_vault_account
             .key
-            .eq(&whitelist_global_state.token_swap_pool_state)
+            .eq(&whitelist_global_state.y_token_account)
         {
-            msg!("Whitelist SwapSOL: Token Swap State Account mismatch");
-            return Err(ProgramError::InvalidAccountData);
+            msg!("Whitelist WithdrawUnsoldTokens: Y Token vault mismatch");
+            return Err(VaultMismatch.into());
         }
 
-        // Checking that the user's y token account should be empty
-        if user_wlst_token_state.amount.gt(&0u64) {
-            msg!("Whitelist SwapSOL: User already redeemed SPL");
-            return Err(AccountAlreadyRedeemed.into());
+        // Tokens escrowed for the buyers of a claim later sale have to stay in the vault,
+        // the vesting ones already moved to the buyers' vesting vaults
+        let y_vault_state = TokenState::unpack(&y_vault_account.data.borrow())?;
+        let unsold_amount = y_vault_state
+            .amount
+            .saturating_sub(whitelist_global_state.total_y_escrowed);
+
+        if amount == 0 || amount.gt(&unsold_amount) {
+            msg!(
+                "Whitelist WithdrawUnsoldTokens: Amount should be between 1 and {} Y tokens",
+                unsold_amount.to_string()
+            );
+            return Err(InvalidWithdrawAmount.into());
         }
 
-        msg!("Starting the Swap procedure");
-        let native_sol_to_spl_swap_ix = swap(
-            &token_swap_program_id(),
+        // The token program checks the destination's mint against the vault's
+        msg!("Transferring the unsold Y tokens from the vault");
+        let transfer_y_tokens_ix = transfer(
             &token_program_id(),
-            &token_swap_state_account.key,
-            &swap_authority_pda_account.key,
-            &user_temporary_auth_token_account.key,
-            &user_native_sol_token_account.key,
-            &token_swap_native_sol_token_account.key,
-            &token_swap_wlst_token_account.key,
-            &user_wlst_token_account.key,
-            &pool_mint_token_account.key,
-            &pool_token_fee_account.key,
-            Some(&pool_owner_account.key),
-            Swap {
-                amount_in: input_sol_amount,
-                minimum_amount_out: expected_spl_token_amount,
-            },
+            y_vault_account.key,
+            destination_token_account.key,
+            whitelist_global_state_account.key,
+            &[],
+            amount,
         )?;
 
-        invoke(
-            &native_sol_to_spl_swap_ix,
+        invoke_signed_by_whitelist_pda(
+            &transfer_y_tokens_ix,
             &[
-                token_swap_state_account.clone(),
-                swap_authority_pda_account.clone(),
-                user_temporary_auth_token_account.clone(),
-                user_native_sol_token_account.clone(),
-                token_swap_native_sol_token_account.clone(),
-                token_swap_wlst_token_account.clone(),
-                user_wlst_token_account.clone(),
-                pool_mint_token_account.clone(),
-                pool_token_fee_account.clone(),
+                y_vault_account.clone(),
+                destination_token_account.clone(),
+                whitelist_global_state_account.clone(),
                 token_program_account.clone(),
-                pool_owner_account.clone(),
-                token_swap_program_account.clone(),
             ],
+            &whitelist_global_state,
         )?;
 
-        msg!("Assigning state for the whitelist user");
-        whitelist_user_state.is_initialized = true;
-        whitelist_user_state.whitelisted_by_account = *user_account.key;
-        whitelist_user_state.whitelisted_at = current_network_time;
-        whitelist_user_state.user_transfer_authority_account =
-            *user_temporary_auth_token_account.key;
-
-        whitelist_user_state
-            .serialize(&mut &mut whitelist_user_state_account.data.borrow_mut()[..])?;
+        msg!(
+            "Withdrew {} unsold Y tokens to {}",
+            amount.to_string(),
+            destination_token_account.key.to_string()
+        );
 
         Ok(())
     }
@@ -808,15 +4328,25 @@ impl WhiteListProcessor {
 
         match whitelist_instrution {
             WhiteListInstruction::InitWhiteList {
-                authorized_addresses,
+                merkle_root,
                 price_per_token_y,
-                whitelist_pda_bump,
+                min_contribution,
+                max_contribution,
+                sale_start_ts,
+                sale_end_ts,
+                is_fixed_price,
+                sale_id,
             } => {
                 msg!("Instruction: Whitelist Initialize");
                 Self::process_whitelist_initialize(
-                    authorized_addresses,
-                    whitelist_pda_bump,
+                    merkle_root,
                     price_per_token_y,
+                    min_contribution,
+                    max_contribution,
+                    sale_start_ts,
+                    sale_end_ts,
+                    is_fixed_price,
+                    sale_id,
                     program_id,
                     accounts,
                 )
@@ -826,14 +4356,18 @@ impl WhiteListProcessor {
                 amount_to_be_wrapped,
             } => {
                 msg!("Instruction: Whitelist Create and Wrap SOL");
-                Self::process_whitelist_create_and_wrap_sol(amount_to_be_wrapped, accounts)
+                Self::process_whitelist_create_and_wrap_sol(
+                    amount_to_be_wrapped,
+                    accounts,
+                    program_id,
+                )
             }
 
             WhiteListInstruction::WrapSOLToken {
                 amount_to_be_wrapped,
             } => {
                 msg!("Instruction: Whitelist Wrap SOL");
-                Self::process_whitelist_wrap_sol(amount_to_be_wrapped, accounts)
+                Self::process_whitelist_wrap_sol(amount_to_be_wrapped, accounts, program_id)
             }
 
             WhiteListInstruction::UnwrapSOLToken => {
@@ -844,15 +4378,237 @@ impl WhiteListProcessor {
             WhiteListInstruction::SwapSOLToken {
                 expected_spl_token_amount,
                 input_sol_amount,
+                allocation,
+                proof,
             } => {
                 msg!("Instruction: Whitelist Swap SOL");
                 Self::process_whitelist_swap_sol(
                     input_sol_amount,
                     expected_spl_token_amount,
+                    allocation,
+                    proof,
+                    accounts,
+                    program_id,
+                )
+            }
+
+            WhiteListInstruction::AddToWhitelist { allocation } => {
+                msg!("Instruction: Whitelist Add Entry");
+                Self::process_whitelist_add_entry(allocation, accounts, program_id)
+            }
+
+            WhiteListInstruction::RemoveFromWhitelist => {
+                msg!("Instruction: Whitelist Remove Entry");
+                Self::process_whitelist_remove_entry(accounts, program_id)
+            }
+
+            WhiteListInstruction::SetWhitelistAdmin => {
+                msg!("Instruction: Whitelist Set Admin");
+                Self::process_whitelist_set_admin(accounts, program_id)
+            }
+
+            WhiteListInstruction::SetSaleSchedule {
+                sale_start_ts,
+                sale_end_ts,
+                phases,
+            } => {
+                msg!("Instruction: Whitelist Set Sale Schedule");
+                Self::process_whitelist_set_sale_schedule(
+                    sale_start_ts,
+                    sale_end_ts,
+                    phases,
+                    accounts,
+                    program_id,
+                )
+            }
+
+            WhiteListInstruction::UpdateWhitelistConfig {
+                price_per_token_y,
+                is_fixed_price,
+            } => {
+                msg!("Instruction: Whitelist Update Config");
+                Self::process_whitelist_update_config(
+                    price_per_token_y,
+                    is_fixed_price,
+                    accounts,
+                    program_id,
+                )
+            }
+
+            WhiteListInstruction::Pause => {
+                msg!("Instruction: Whitelist Pause");
+                Self::process_whitelist_set_paused(true, accounts, program_id)
+            }
+
+            WhiteListInstruction::Resume => {
+                msg!("Instruction: Whitelist Resume");
+                Self::process_whitelist_set_paused(false, accounts, program_id)
+            }
+
+            WhiteListInstruction::SetGuardian => {
+                msg!("Instruction: Whitelist Set Guardian");
+                Self::process_whitelist_set_guardian(accounts, program_id)
+            }
+
+            WhiteListInstruction::ProposeAuthority => {
+                msg!("Instruction: Whitelist Propose Authority");
+                Self::process_whitelist_propose_authority(accounts, program_id)
+            }
+
+            WhiteListInstruction::AcceptAuthority => {
+                msg!("Instruction: Whitelist Accept Authority");
+                Self::process_whitelist_accept_authority(accounts, program_id)
+            }
+
+            WhiteListInstruction::CloseWhitelist => {
+                msg!("Instruction: Whitelist Close");
+                Self::process_whitelist_close(accounts, program_id)
+            }
+
+            WhiteListInstruction::CloseUserState => {
+                msg!("Instruction: Whitelist Close User State");
+                Self::process_whitelist_close_user_state(accounts, program_id)
+            }
+
+            WhiteListInstruction::SetTreasury => {
+                msg!("Instruction: Whitelist Set Treasury");
+                Self::process_whitelist_set_treasury(accounts, program_id)
+            }
+
+            WhiteListInstruction::WithdrawProceeds {
+                amount,
+                maximum_pool_token_amount,
+            } => {
+                msg!("Instruction: Whitelist Withdraw Proceeds");
+                Self::process_whitelist_withdraw_proceeds(
+                    amount,
+                    maximum_pool_token_amount,
+                    accounts,
+                    program_id,
+                )
+            }
+
+            WhiteListInstruction::InitDirectSale {
+                price_per_token_y,
+                merkle_root,
+                min_contribution,
+                max_contribution,
+                sale_start_ts,
+                sale_end_ts,
+                sale_id,
+            } => {
+                msg!("Instruction: Whitelist Initialize Direct Sale");
+                Self::process_whitelist_initialize_direct_sale(
+                    merkle_root,
+                    price_per_token_y,
+                    min_contribution,
+                    max_contribution,
+                    sale_start_ts,
+                    sale_end_ts,
+                    sale_id,
+                    program_id,
+                    accounts,
+                )
+            }
+
+            WhiteListInstruction::BuyDirect {
+                input_sol_amount,
+                allocation,
+                proof,
+            } => {
+                msg!("Instruction: Whitelist Buy Direct");
+                Self::process_whitelist_buy_direct(
+                    input_sol_amount,
+                    allocation,
+                    proof,
+                    accounts,
+                    program_id,
+                )
+            }
+
+            WhiteListInstruction::WithdrawDirectProceeds { amount } => {
+                msg!("Instruction: Whitelist Withdraw Direct Proceeds");
+                Self::process_whitelist_withdraw_direct_proceeds(amount, accounts, program_id)
+            }
+
+            WhiteListInstruction::SetSaleCaps {
+                soft_cap,
+                hard_cap,
+                is_claim_later,
+            } => {
+                msg!("Instruction: Whitelist Set Sale Caps");
+                Self::process_whitelist_set_sale_caps(
+                    soft_cap,
+                    hard_cap,
+                    is_claim_later,
+                    accounts,
+                    program_id,
+                )
+            }
+
+            WhiteListInstruction::Refund => {
+                msg!("Instruction: Whitelist Refund");
+                Self::process_whitelist_refund(accounts, program_id)
+            }
+
+            WhiteListInstruction::ClaimTokens => {
+                msg!("Instruction: Whitelist Claim Tokens");
+                Self::process_whitelist_claim_tokens(accounts, program_id)
+            }
+
+            WhiteListInstruction::SetVestingSchedule {
+                is_vesting,
+                vesting_cliff_ts,
+                vesting_duration,
+                vesting_tge_unlock_bps,
+            } => {
+                msg!("Instruction: Whitelist Set Vesting Schedule");
+                Self::process_whitelist_set_vesting_schedule(
+                    is_vesting,
+                    vesting_cliff_ts,
+                    vesting_duration,
+                    vesting_tge_unlock_bps,
+                    accounts,
+                    program_id,
+                )
+            }
+
+            WhiteListInstruction::CreateVestingVault => {
+                msg!("Instruction: Whitelist Create Vesting Vault");
+                Self::process_whitelist_create_vesting_vault(accounts, program_id)
+            }
+
+            WhiteListInstruction::ClaimVested => {
+                msg!("Instruction: Whitelist Claim Vested");
+                Self::process_whitelist_claim_vested(accounts, program_id)
+            }
+
+            WhiteListInstruction::BuyWithSOL {
+                input_sol_amount,
+                expected_spl_token_amount,
+                allocation,
+                proof,
+            } => {
+                msg!("Instruction: Whitelist Buy With SOL");
+                Self::process_whitelist_buy_with_sol(
+                    input_sol_amount,
+                    expected_spl_token_amount,
+                    allocation,
+                    proof,
                     accounts,
                     program_id,
                 )
             }
+
+            WhiteListInstruction::MigrateGlobalState => {
+                msg!("Instruction: Whitelist Migrate Global State");
+                Self::process_whitelist_migrate_global_state(accounts, program_id)
+            }
+
+            WhiteListInstruction::WithdrawUnsoldTokens { amount } => {
+                msg!("Instruction: Whitelist Withdraw Unsold Tokens");
+                Self::process_whitelist_withdraw_unsold_tokens(amount, accounts, program_id)
+            }
         }
     }
 }
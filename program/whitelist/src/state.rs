@@ -1,8 +1,171 @@
+use crate::error::WhiteListError::InvalidStateVersion;
+use crate::merkle::{AllowlistEntry, MerkleTree};
 use borsh::{self, BorshDeserialize, BorshSerialize};
-use solana_program::{program_pack::IsInitialized, pubkey::Pubkey};
+use solana_program::{
+    clock::UnixTimestamp, program_error::ProgramError, program_pack::IsInitialized, pubkey::Pubkey,
+};
+use spl_token::native_mint;
 
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub const MAX_SALE_PHASES: usize = 4;
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+pub const MAX_REGISTRY_SALES: usize = 32;
+
+/// Layout version of `WhitelistPDAGlobalState`, stored in its first byte.
+/// Version 1 accounts predate the version byte and are migrated with `MigrateGlobalState`.
+pub const GLOBAL_STATE_VERSION: u8 = 2;
+/// Size of a version 1 global state account, see `WhitelistPDAGlobalStateV1`
+pub const GLOBAL_STATE_V1_LEN: usize = 362;
+
+/// Size of the Borsh encoding of a state account. None of the layouts holds a variable
+/// length field, so the encoding of the default value has the size of any other.
+pub fn get_state_packed_len<T: Default + BorshSerialize>() -> usize {
+    // Serializing into a Vec can't fail
+    T::default().try_to_vec().unwrap().len()
+}
+
+/// A phase of the sale, running from `start_ts` until the next phase starts or the sale ends.
+/// Zero price and maximum contribution fall back to the global values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SalePhase {
+    pub start_ts: UnixTimestamp,
+    pub price_per_token_y: u64,
+    pub max_contribution: u64,
+    pub is_public: bool,
+}
+
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize)]
 pub struct WhitelistPDAGlobalState {
+    pub version: u8,
+    pub whitelist_creator: Pubkey,
+    pub global_pda_bump: u8,
+    pub whitelist_merkle_root: [u8; 32],
+    pub is_initialized: bool,
+    pub token_swap_pool_state: Pubkey,
+    pub y_mint_account: Pubkey,
+    pub y_token_account: Pubkey,
+    // The pool's token account on the side users pay with
+    pub quote_token_account: Pubkey,
+    pub price_per_token_y: u64,
+    pub whitelist_admin: Pubkey,
+    // A smaller purchase is still allowed when it exactly fills the rest of the user's
+    // maximum contribution, so the last bit of an allocation can always be bought
+    pub min_contribution: u64,
+    pub max_contribution: u64,
+    pub sale_start_ts: UnixTimestamp,
+    pub sale_end_ts: UnixTimestamp,
+    pub sale_phase_count: u8,
+    pub sale_phases: [SalePhase; MAX_SALE_PHASES],
+    pub is_fixed_price: bool,
+    pub is_paused: bool,
+    pub whitelist_guardian: Pubkey,
+    pub whitelist_authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub treasury: Pubkey,
+    pub total_proceeds_withdrawn: u64,
+    // The PDA address stays derived from the Y token account it was created with,
+    // even once the pool has been rebound
+    pub seed_y_token_account: Pubkey,
+    // Direct sales sell from a Y token vault owned by the PDA instead of a token swap pool
+    pub is_direct_sale: bool,
+    // Caps on the total SOL raised, 0 meaning no cap
+    pub soft_cap: u64,
+    pub hard_cap: u64,
+    pub total_raised: u64,
+    pub total_refunded: u64,
+    // Claim later sales escrow the Y tokens bought in the vault until the sale ends
+    pub is_claim_later: bool,
+    pub total_y_escrowed: u64,
+    // Vesting sales lock the Y tokens bought in per user vaults, releasing the TGE share
+    // right away and the rest linearly over `vesting_duration` seconds from the cliff
+    pub is_vesting: bool,
+    pub vesting_cliff_ts: UnixTimestamp,
+    pub vesting_duration: i64,
+    pub vesting_tge_unlock_bps: u16,
+    pub total_y_vesting: u64,
+    // Tells apart the sales a creator runs for the same Y token account, part of the PDA seeds
+    pub sale_id: u64,
+    // The mint contributions are paid in (wSOL, USDC, ...), prices and caps being in its
+    // smallest units. Direct sales are always paid in lamports, with the native mint.
+    pub quote_mint: Pubkey,
+    // Sales migrated from version 1 keep the address they were created at, derived from
+    // the creator and Y token account only and with the bump they were given
+    pub has_legacy_seeds: bool,
+}
+
+impl IsInitialized for WhitelistPDAGlobalState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl WhitelistPDAGlobalState {
+    /// Unpacks a global state at the current layout version
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.first() != Some(&GLOBAL_STATE_VERSION) {
+            return Err(InvalidStateVersion.into());
+        }
+
+        Self::try_from_slice(data).map_err(|_| InvalidStateVersion.into())
+    }
+
+    /// Unpacks a version 1 global state into the current layout
+    pub fn unpack_v1(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != GLOBAL_STATE_V1_LEN {
+            return Err(InvalidStateVersion.into());
+        }
+
+        WhitelistPDAGlobalStateV1::try_from_slice(data)
+            .map(Self::from)
+            .map_err(|_| InvalidStateVersion.into())
+    }
+
+    /// The phase running at `timestamp`, if the sale has a phase schedule
+    pub fn current_phase(&self, timestamp: UnixTimestamp) -> Option<&SalePhase> {
+        self.sale_phases[..self.sale_phase_count as usize]
+            .iter()
+            .rev()
+            .find(|phase| phase.start_ts <= timestamp)
+    }
+
+    /// Whether the sale has an end time and it has passed at `timestamp`
+    pub fn has_ended(&self, timestamp: UnixTimestamp) -> bool {
+        self.sale_end_ts != 0 && timestamp >= self.sale_end_ts
+    }
+
+    /// Whether the sale has a soft cap it has not (yet) reached
+    pub fn is_below_soft_cap(&self) -> bool {
+        self.soft_cap > 0 && self.total_raised < self.soft_cap
+    }
+
+    /// How much of `amount` vested Y tokens is unlocked at `timestamp`
+    pub fn vested_amount(&self, amount: u64, timestamp: UnixTimestamp) -> u64 {
+        if !self.is_vesting {
+            return amount;
+        }
+
+        let tge_amount = (amount as u128 * self.vesting_tge_unlock_bps as u128
+            / MAX_BASIS_POINTS as u128) as u64;
+
+        if timestamp < self.vesting_cliff_ts {
+            return tge_amount;
+        }
+
+        let elapsed = timestamp.saturating_sub(self.vesting_cliff_ts);
+
+        if elapsed >= self.vesting_duration {
+            return amount;
+        }
+
+        let locked_amount = amount - tge_amount;
+        tge_amount
+            + (locked_amount as u128 * elapsed as u128 / self.vesting_duration as u128) as u64
+    }
+}
+
+/// Layout of the global state accounts created before the layout had a version byte,
+/// only kept around to migrate them
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct WhitelistPDAGlobalStateV1 {
     pub whitelist_creator: Pubkey,
     pub global_pda_bump: u8,
     pub whitelist_auth_addresses: [Pubkey; 6],
@@ -14,22 +177,167 @@ pub struct WhitelistPDAGlobalState {
     pub price_per_token_y: u64,
 }
 
-impl IsInitialized for WhitelistPDAGlobalState {
+impl From<WhitelistPDAGlobalStateV1> for WhitelistPDAGlobalState {
+    fn from(state: WhitelistPDAGlobalStateV1) -> Self {
+        // The addresses allowed to buy become an allowlist without per-user caps, the
+        // processor keeping the version 1 single purchase for sales with legacy seeds
+        let allowlist: Vec<AllowlistEntry> = state
+            .whitelist_auth_addresses
+            .iter()
+            .filter(|&&user| user != Pubkey::default())
+            .map(|&user| AllowlistEntry {
+                user,
+                allocation: 0,
+            })
+            .collect();
+
+        Self {
+            version: GLOBAL_STATE_VERSION,
+            whitelist_creator: state.whitelist_creator,
+            global_pda_bump: state.global_pda_bump,
+            whitelist_merkle_root: MerkleTree::new(&allowlist).root(),
+            is_initialized: state.is_initialized,
+            token_swap_pool_state: state.token_swap_pool_state,
+            y_mint_account: state.y_mint_account,
+            y_token_account: state.y_token_account,
+            quote_token_account: state.native_sol_token_account,
+            price_per_token_y: state.price_per_token_y,
+            whitelist_admin: state.whitelist_creator,
+            whitelist_authority: state.whitelist_creator,
+            treasury: state.whitelist_creator,
+            seed_y_token_account: state.y_token_account,
+            quote_mint: native_mint::id(),
+            has_legacy_seeds: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Lists the global state accounts of the sales a creator has running
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct WhitelistCreatorRegistry {
+    pub is_initialized: bool,
+    pub whitelist_creator: Pubkey,
+    pub registry_bump: u8,
+    pub sale_count: u8,
+    pub sales: [Pubkey; MAX_REGISTRY_SALES],
+}
+
+impl IsInitialized for WhitelistCreatorRegistry {
     fn is_initialized(&self) -> bool {
-        return self.is_initialized;
+        self.is_initialized
+    }
+}
+
+impl WhitelistCreatorRegistry {
+    /// The global state accounts of the sales listed
+    pub fn sales(&self) -> &[Pubkey] {
+        &self.sales[..self.sale_count as usize]
     }
 }
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
+#[derive(Debug, Default, BorshDeserialize, BorshSerialize)]
 pub struct WhitelistUserState {
     pub is_initialized: bool,
     pub whitelisted_by_account: Pubkey,
     pub whitelisted_at: i64,
     pub user_transfer_authority_account: Pubkey,
+    pub total_contributed: u64,
+    pub total_received: u64,
+    pub user_state_bump: u8,
+    pub total_refunded: u64,
+    pub total_claimed: u64,
 }
 
 impl IsInitialized for WhitelistUserState {
     fn is_initialized(&self) -> bool {
-        return self.is_initialized;
+        self.is_initialized
+    }
+}
+
+#[derive(Debug, Default, BorshDeserialize, BorshSerialize)]
+pub struct WhitelistEntryState {
+    pub is_initialized: bool,
+    pub whitelist_global_state: Pubkey,
+    pub user: Pubkey,
+    pub allocation: u64,
+    pub entry_bump: u8,
+}
+
+impl IsInitialized for WhitelistEntryState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_global_state_versions() {
+        let state = WhitelistPDAGlobalState {
+            version: GLOBAL_STATE_VERSION,
+            whitelist_creator: Pubkey::new_unique(),
+            is_initialized: true,
+            sale_id: 1,
+            ..WhitelistPDAGlobalState::default()
+        };
+        let packed = state.try_to_vec().unwrap();
+        assert_eq!(
+            packed.len(),
+            get_state_packed_len::<WhitelistPDAGlobalState>()
+        );
+
+        let unpacked = WhitelistPDAGlobalState::unpack(&packed).unwrap();
+        assert_eq!(unpacked.whitelist_creator, state.whitelist_creator);
+        assert!(!unpacked.has_legacy_seeds);
+
+        // A version 1 account has the layout the program was first deployed with
+        let user = Pubkey::new_unique();
+        let mut whitelist_auth_addresses = [Pubkey::default(); 6];
+        whitelist_auth_addresses[0] = user;
+        let v1_state = WhitelistPDAGlobalStateV1 {
+            whitelist_creator: Pubkey::new_unique(),
+            global_pda_bump: 254,
+            whitelist_auth_addresses,
+            is_initialized: true,
+            y_token_account: Pubkey::new_unique(),
+            native_sol_token_account: Pubkey::new_unique(),
+            price_per_token_y: 100,
+            ..WhitelistPDAGlobalStateV1::default()
+        };
+        let v1_packed = v1_state.try_to_vec().unwrap();
+        assert_eq!(v1_packed.len(), GLOBAL_STATE_V1_LEN);
+        assert_eq!(
+            v1_packed.len(),
+            get_state_packed_len::<WhitelistPDAGlobalStateV1>()
+        );
+
+        let migrated = WhitelistPDAGlobalState::unpack_v1(&v1_packed).unwrap();
+        assert_eq!(migrated.version, GLOBAL_STATE_VERSION);
+        assert_eq!(migrated.whitelist_creator, v1_state.whitelist_creator);
+        assert_eq!(migrated.whitelist_authority, v1_state.whitelist_creator);
+        assert_eq!(migrated.global_pda_bump, v1_state.global_pda_bump);
+        assert_eq!(migrated.seed_y_token_account, v1_state.y_token_account);
+        assert_eq!(
+            migrated.quote_token_account,
+            v1_state.native_sol_token_account
+        );
+        assert_eq!(migrated.quote_mint, native_mint::id());
+        assert_eq!(migrated.price_per_token_y, v1_state.price_per_token_y);
+        assert_eq!(migrated.sale_id, 0);
+        assert!(migrated.has_legacy_seeds);
+
+        // Users allowed by version 1 are on the migrated allowlist, without a cap
+        let root = MerkleTree::new(&[AllowlistEntry {
+            user,
+            allocation: 0,
+        }])
+        .root();
+        assert_eq!(migrated.whitelist_merkle_root, root);
+
+        assert!(WhitelistPDAGlobalState::unpack(&v1_packed).is_err());
+        assert!(WhitelistPDAGlobalState::unpack_v1(&packed).is_err());
     }
 }
@@ -0,0 +1,2317 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{program_pack::Pack, pubkey::Pubkey, rent::Rent, system_instruction};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use spl_token::{native_mint, state::Account as TokenState, state::Mint};
+use spl_token_swap::curve::{
+    base::{CurveType, SwapCurve},
+    constant_price::ConstantPriceCurve,
+    fees::Fees,
+};
+use whitelist_program::{
+    error::WhiteListError,
+    find_whitelist_address, find_whitelist_registry_address, find_whitelist_user_state_address,
+    find_whitelist_vesting_vault_address, instruction,
+    merkle::{AllowlistEntry, MerkleTree},
+    processor::WhiteListProcessor,
+    state::{
+        WhitelistCreatorRegistry, WhitelistPDAGlobalState, WhitelistPDAGlobalStateV1,
+        WhitelistUserState, GLOBAL_STATE_V1_LEN, GLOBAL_STATE_VERSION,
+    },
+    WHITELIST_PDA_SEED,
+};
+
+// Lamports of native SOL per smallest unit of Y, matching the constant price curve of the pool
+const PRICE_PER_TOKEN_Y: u64 = 1_000_000;
+const POOL_NATIVE_SOL_LIQUIDITY: u64 = 1_000_000_000;
+const POOL_Y_LIQUIDITY: u64 = 1_000_000;
+const USER_ALLOCATION: u64 = 10 * PRICE_PER_TOKEN_Y;
+
+struct Pool {
+    state: Keypair,
+    y_mint: Pubkey,
+    quote_mint: Pubkey,
+    quote_token_account: Pubkey,
+    y_token_account: Pubkey,
+    pool_mint: Pubkey,
+    pool_fee_account: Pubkey,
+    host_fee_account: Pubkey,
+}
+
+fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "whitelist_program",
+        whitelist_program::id(),
+        processor!(WhiteListProcessor::process),
+    );
+
+    program_test.add_program(
+        "spl_token_swap",
+        spl_token_swap::id(),
+        processor!(spl_token_swap::processor::Processor::process),
+    );
+
+    program_test
+}
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[solana_program::instruction::Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+
+    let all_signers = [&[payer], signers].concat();
+
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn create_mint(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint_authority: &Pubkey,
+    decimals: u8,
+) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                mint_authority,
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ],
+        &[&mint],
+    )
+    .await
+    .unwrap();
+
+    mint.pubkey()
+}
+
+// Native SOL token accounts get `native_amount` lamports on top of the rent exempt reserve
+async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    native_amount: u64,
+) -> Pubkey {
+    let token_account = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &token_account.pubkey(),
+                rent.minimum_balance(TokenState::LEN) + native_amount,
+                TokenState::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &token_account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&token_account],
+    )
+    .await
+    .unwrap();
+
+    token_account.pubkey()
+}
+
+async fn get_token_balance(banks_client: &mut BanksClient, token_account: &Pubkey) -> u64 {
+    let account = banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap();
+
+    TokenState::unpack(&account.data).unwrap().amount
+}
+
+async fn mint_to(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    amount: u64,
+) {
+    process(
+        banks_client,
+        payer,
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            token_account,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+// Sets up a constant price native SOL / Y pool, the same way `createPool.ts` does
+async fn create_pool(banks_client: &mut BanksClient, payer: &Keypair) -> Pool {
+    create_quoted_pool(banks_client, payer, &native_mint::id()).await
+}
+
+// Sets up a constant price pool pairing Y with `quote_mint`, minted by the payer unless native
+async fn create_quoted_pool(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    quote_mint: &Pubkey,
+) -> Pool {
+    let state = Keypair::new();
+    let (authority, _) =
+        Pubkey::find_program_address(&[&state.pubkey().to_bytes()], &spl_token_swap::id());
+
+    let y_mint = create_mint(banks_client, payer, &payer.pubkey(), 2).await;
+    let quote_token_account = if quote_mint.eq(&native_mint::id()) {
+        create_token_account(
+            banks_client,
+            payer,
+            quote_mint,
+            &authority,
+            POOL_NATIVE_SOL_LIQUIDITY,
+        )
+        .await
+    } else {
+        let quote_token_account =
+            create_token_account(banks_client, payer, quote_mint, &authority, 0).await;
+        mint_to(
+            banks_client,
+            payer,
+            quote_mint,
+            &quote_token_account,
+            POOL_NATIVE_SOL_LIQUIDITY,
+        )
+        .await;
+        quote_token_account
+    };
+    let y_token_account = create_token_account(banks_client, payer, &y_mint, &authority, 0).await;
+
+    mint_to(
+        banks_client,
+        payer,
+        &y_mint,
+        &y_token_account,
+        POOL_Y_LIQUIDITY,
+    )
+    .await;
+
+    let pool_mint = create_mint(banks_client, payer, &authority, 2).await;
+    let pool_fee_account =
+        create_token_account(banks_client, payer, &pool_mint, &payer.pubkey(), 0).await;
+    let host_fee_account =
+        create_token_account(banks_client, payer, &pool_mint, &payer.pubkey(), 0).await;
+
+    let rent = banks_client.get_rent().await.unwrap();
+    let fees = Fees {
+        trade_fee_numerator: 0,
+        trade_fee_denominator: 1,
+        owner_trade_fee_numerator: 0,
+        owner_trade_fee_denominator: 1,
+        owner_withdraw_fee_numerator: 0,
+        owner_withdraw_fee_denominator: 1,
+        host_fee_numerator: 0,
+        host_fee_denominator: 1,
+    };
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantPrice,
+        calculator: Box::new(ConstantPriceCurve {
+            token_b_price: PRICE_PER_TOKEN_Y,
+        }),
+    };
+
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &state.pubkey(),
+                rent.minimum_balance(spl_token_swap::state::SwapVersion::LATEST_LEN),
+                spl_token_swap::state::SwapVersion::LATEST_LEN as u64,
+                &spl_token_swap::id(),
+            ),
+            spl_token_swap::instruction::initialize(
+                &spl_token_swap::id(),
+                &spl_token::id(),
+                &state.pubkey(),
+                &authority,
+                &quote_token_account,
+                &y_token_account,
+                &pool_mint,
+                &pool_fee_account,
+                &host_fee_account,
+                fees,
+                swap_curve,
+            )
+            .unwrap(),
+        ],
+        &[&state],
+    )
+    .await
+    .unwrap();
+
+    Pool {
+        state,
+        y_mint,
+        quote_mint: *quote_mint,
+        quote_token_account,
+        y_token_account,
+        pool_mint,
+        pool_fee_account,
+        host_fee_account,
+    }
+}
+
+async fn init_whitelist(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    pool: &Pool,
+    merkle_root: [u8; 32],
+) -> Pubkey {
+    process(
+        banks_client,
+        payer,
+        &[instruction::init_whitelist(
+            &payer.pubkey(),
+            &pool.state.pubkey(),
+            &pool.y_mint,
+            &pool.y_token_account,
+            &pool.quote_token_account,
+            PRICE_PER_TOKEN_Y,
+            merkle_root,
+            0,
+            0,
+            0,
+            0,
+            true,
+            0,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    find_whitelist_address(&payer.pubkey(), &pool.y_token_account, 0).0
+}
+
+// Funds a fresh user, wraps SOL for them and creates their Y token account
+async fn create_user(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    pool: &Pool,
+    amount_to_be_wrapped: u64,
+) -> Keypair {
+    let user = Keypair::new();
+
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1_000_000_000),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &user.pubkey(),
+                &pool.y_mint,
+                &spl_token::id(),
+            ),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    process(
+        banks_client,
+        payer,
+        &[instruction::create_and_wrap_sol_token(
+            &user.pubkey(),
+            amount_to_be_wrapped,
+            None,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    user
+}
+
+// Sets up a direct sale selling from a vault owned by the PDA derived from the vault itself
+async fn create_direct_sale(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    merkle_root: [u8; 32],
+    sale_end_ts: i64,
+) -> (Pubkey, Pubkey, Pubkey) {
+    let y_mint = create_mint(banks_client, payer, &payer.pubkey(), 2).await;
+    let y_vault = Keypair::new();
+    let (whitelist_global_state, _) = find_whitelist_address(&payer.pubkey(), &y_vault.pubkey(), 0);
+    let rent = banks_client.get_rent().await.unwrap();
+
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &y_vault.pubkey(),
+                rent.minimum_balance(TokenState::LEN),
+                TokenState::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &y_vault.pubkey(),
+                &y_mint,
+                &whitelist_global_state,
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &y_mint,
+                &y_vault.pubkey(),
+                &payer.pubkey(),
+                &[],
+                POOL_Y_LIQUIDITY,
+            )
+            .unwrap(),
+            instruction::init_direct_sale(
+                &payer.pubkey(),
+                &y_mint,
+                &y_vault.pubkey(),
+                PRICE_PER_TOKEN_Y,
+                merkle_root,
+                0,
+                0,
+                0,
+                sale_end_ts,
+                0,
+            ),
+        ],
+        &[&y_vault],
+    )
+    .await
+    .unwrap();
+
+    (whitelist_global_state, y_mint, y_vault.pubkey())
+}
+
+fn swap_sol_token(
+    user: &Keypair,
+    whitelist_global_state: &Pubkey,
+    pool: &Pool,
+    input_sol_amount: u64,
+    allocation: u64,
+    proof: Vec<[u8; 32]>,
+) -> solana_program::instruction::Instruction {
+    instruction::swap_sol_token(
+        &user.pubkey(),
+        whitelist_global_state,
+        &pool.y_mint,
+        &pool.quote_mint,
+        &user.pubkey(),
+        &pool.state.pubkey(),
+        &pool.quote_token_account,
+        &pool.y_token_account,
+        &pool.pool_mint,
+        &pool.pool_fee_account,
+        &pool.host_fee_account,
+        input_sol_amount,
+        input_sol_amount / PRICE_PER_TOKEN_Y,
+        allocation,
+        proof,
+        false,
+        false,
+    )
+}
+
+#[tokio::test]
+async fn test_init_whitelist() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+
+    let merkle_root = [7u8; 32];
+    let whitelist_global_state =
+        init_whitelist(&mut banks_client, &payer, &pool, merkle_root).await;
+
+    let account = banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, whitelist_program::id());
+
+    let state = WhitelistPDAGlobalState::try_from_slice(&account.data).unwrap();
+    assert!(state.is_initialized);
+    assert_eq!(state.whitelist_creator, payer.pubkey());
+    assert_eq!(state.whitelist_authority, payer.pubkey());
+    assert_eq!(state.whitelist_merkle_root, merkle_root);
+    assert_eq!(state.token_swap_pool_state, pool.state.pubkey());
+    assert_eq!(state.y_mint_account, pool.y_mint);
+    assert_eq!(state.y_token_account, pool.y_token_account);
+    assert_eq!(state.quote_token_account, pool.quote_token_account);
+    assert_eq!(state.quote_mint, native_mint::id());
+    assert_eq!(state.price_per_token_y, PRICE_PER_TOKEN_Y);
+    assert!(state.is_fixed_price);
+
+    // Initializing the same whitelist twice fails
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::init_whitelist(
+            &payer.pubkey(),
+            &pool.state.pubkey(),
+            &pool.y_mint,
+            &pool.y_token_account,
+            &pool.quote_token_account,
+            PRICE_PER_TOKEN_Y,
+            merkle_root,
+            0,
+            0,
+            0,
+            0,
+            false,
+            0,
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+
+    // The Y mint passed has to be the one the pool's Y token account holds
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::init_whitelist(
+            &payer.pubkey(),
+            &pool.state.pubkey(),
+            &pool.quote_mint,
+            &pool.y_token_account,
+            &pool.quote_token_account,
+            PRICE_PER_TOKEN_Y,
+            merkle_root,
+            0,
+            0,
+            0,
+            0,
+            true,
+            2,
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::IncorrectMint as u32)
+        )
+    );
+
+    // A fixed price sale has to sell at the price of the pool's constant price curve
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::init_whitelist(
+            &payer.pubkey(),
+            &pool.state.pubkey(),
+            &pool.y_mint,
+            &pool.y_token_account,
+            &pool.quote_token_account,
+            PRICE_PER_TOKEN_Y + 1,
+            merkle_root,
+            0,
+            0,
+            0,
+            0,
+            true,
+            2,
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::PriceMismatch as u32)
+        )
+    );
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::update_whitelist_config(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &pool.state.pubkey(),
+            &pool.y_mint,
+            &pool.y_token_account,
+            &pool.quote_token_account,
+            PRICE_PER_TOKEN_Y + 1,
+            true,
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::PriceMismatch as u32)
+        )
+    );
+
+    // Another round for the same pool runs side by side under a new sale id
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::init_whitelist(
+            &payer.pubkey(),
+            &pool.state.pubkey(),
+            &pool.y_mint,
+            &pool.y_token_account,
+            &pool.quote_token_account,
+            PRICE_PER_TOKEN_Y,
+            merkle_root,
+            0,
+            0,
+            0,
+            0,
+            true,
+            1,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let (second_whitelist_global_state, _) =
+        find_whitelist_address(&payer.pubkey(), &pool.y_token_account, 1);
+    let account = banks_client
+        .get_account(find_whitelist_registry_address(&payer.pubkey()).0)
+        .await
+        .unwrap()
+        .unwrap();
+    let registry = WhitelistCreatorRegistry::try_from_slice(&account.data).unwrap();
+    assert_eq!(registry.whitelist_creator, payer.pubkey());
+    assert_eq!(
+        registry.sales(),
+        &[whitelist_global_state, second_whitelist_global_state]
+    );
+
+    // The sale can't be rebound to a pool selling another Y mint
+    let other_pool = create_pool(&mut banks_client, &payer).await;
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::update_whitelist_config(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &other_pool.state.pubkey(),
+            &other_pool.y_mint,
+            &other_pool.y_token_account,
+            &other_pool.quote_token_account,
+            PRICE_PER_TOKEN_Y,
+            true,
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::MintMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_wrap_and_unwrap_sol() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+
+    let user = create_user(&mut banks_client, &payer, &pool, 5_000_000).await;
+    let native_sol_token_account = get_associated_token_address(&user.pubkey(), &native_mint::id());
+    assert_eq!(
+        get_token_balance(&mut banks_client, &native_sol_token_account).await,
+        5_000_000
+    );
+
+    // Wrapping more SOL into the existing account
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::wrap_sol_token(&user.pubkey(), 2_000_000, None)],
+        &[&user],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_token_balance(&mut banks_client, &native_sol_token_account).await,
+        7_000_000
+    );
+
+    // Unwrapping closes the account and returns the SOL to the user
+    let balance_before = banks_client.get_balance(user.pubkey()).await.unwrap();
+    let token_account_lamports = banks_client
+        .get_account(native_sol_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::unwrap_sol_token(&user.pubkey())],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    assert!(banks_client
+        .get_account(native_sol_token_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        banks_client.get_balance(user.pubkey()).await.unwrap(),
+        balance_before + token_account_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_whitelisted_swap() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+
+    let user = create_user(&mut banks_client, &payer, &pool, USER_ALLOCATION).await;
+    let entries = vec![
+        AllowlistEntry {
+            user: user.pubkey(),
+            allocation: USER_ALLOCATION,
+        },
+        AllowlistEntry {
+            user: Pubkey::new_unique(),
+            allocation: USER_ALLOCATION,
+        },
+    ];
+    let tree = MerkleTree::new(&entries);
+    let whitelist_global_state =
+        init_whitelist(&mut banks_client, &payer, &pool, tree.root()).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &user,
+            &whitelist_global_state,
+            &pool,
+            USER_ALLOCATION,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let expected_y_amount = USER_ALLOCATION / PRICE_PER_TOKEN_Y;
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &pool.y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        expected_y_amount
+    );
+
+    let (whitelist_user_state, _) =
+        find_whitelist_user_state_address(&whitelist_global_state, &user.pubkey());
+    let account = banks_client
+        .get_account(whitelist_user_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = WhitelistUserState::try_from_slice(&account.data).unwrap();
+    assert!(state.is_initialized);
+    assert_eq!(state.whitelisted_by_account, user.pubkey());
+    assert_eq!(state.total_contributed, USER_ALLOCATION);
+    assert_eq!(state.total_received, expected_y_amount);
+
+    // The proceeds stay in the pool while the sale runs
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::withdraw_proceeds(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &pool.state.pubkey(),
+            &pool.pool_mint,
+            &Pubkey::new_unique(),
+            &pool.quote_token_account,
+            &pool.y_token_account,
+            &pool.pool_fee_account,
+            &payer.pubkey(),
+            &native_mint::id(),
+            USER_ALLOCATION,
+            u64::MAX,
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::SaleNotEnded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_non_whitelisted_swap_rejected() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+
+    let whitelisted_user = Pubkey::new_unique();
+    let entries = vec![
+        AllowlistEntry {
+            user: whitelisted_user,
+            allocation: USER_ALLOCATION,
+        },
+        AllowlistEntry {
+            user: Pubkey::new_unique(),
+            allocation: USER_ALLOCATION,
+        },
+    ];
+    let tree = MerkleTree::new(&entries);
+    let whitelist_global_state =
+        init_whitelist(&mut banks_client, &payer, &pool, tree.root()).await;
+
+    // Replaying someone else's proof doesn't work, since the leaf commits to the user
+    let outsider = create_user(&mut banks_client, &payer, &pool, USER_ALLOCATION).await;
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &outsider,
+            &whitelist_global_state,
+            &pool,
+            USER_ALLOCATION,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&outsider],
+    )
+    .await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::AccountNotWhitelisted as u32)
+        )
+    );
+
+    let outsider_y_token_account = get_associated_token_address(&outsider.pubkey(), &pool.y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &outsider_y_token_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_min_contribution() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+
+    let user = create_user(&mut banks_client, &payer, &pool, USER_ALLOCATION).await;
+    let tree = MerkleTree::new(&[AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }]);
+    let min_contribution = 4 * PRICE_PER_TOKEN_Y;
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::init_whitelist(
+            &payer.pubkey(),
+            &pool.state.pubkey(),
+            &pool.y_mint,
+            &pool.y_token_account,
+            &pool.quote_token_account,
+            PRICE_PER_TOKEN_Y,
+            tree.root(),
+            min_contribution,
+            0,
+            0,
+            0,
+            true,
+            0,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let (whitelist_global_state, _) =
+        find_whitelist_address(&payer.pubkey(), &pool.y_token_account, 0);
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &user,
+            &whitelist_global_state,
+            &pool,
+            USER_ALLOCATION - 2 * PRICE_PER_TOKEN_Y,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    // Below the minimum without filling the allocation
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &user,
+            &whitelist_global_state,
+            &pool,
+            PRICE_PER_TOKEN_Y,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::ContributionTooSmall as u32)
+        )
+    );
+
+    // Below the minimum, but exactly what is left of the allocation
+    process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &user,
+            &whitelist_global_state,
+            &pool,
+            2 * PRICE_PER_TOKEN_Y,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &pool.y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        USER_ALLOCATION / PRICE_PER_TOKEN_Y
+    );
+}
+
+#[tokio::test]
+async fn test_double_redeem_rejected() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+
+    let user = create_user(&mut banks_client, &payer, &pool, 2 * USER_ALLOCATION).await;
+    let entries = vec![AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }];
+    let tree = MerkleTree::new(&entries);
+    let whitelist_global_state =
+        init_whitelist(&mut banks_client, &payer, &pool, tree.root()).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &user,
+            &whitelist_global_state,
+            &pool,
+            USER_ALLOCATION,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    // The full allocation has been used, so a second swap is rejected
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &user,
+            &whitelist_global_state,
+            &pool,
+            PRICE_PER_TOKEN_Y,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&user],
+    )
+    .await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::AccountAlreadyRedeemed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_direct_sale() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+
+    let user = Keypair::new();
+    let tree = MerkleTree::new(&[AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }]);
+    let (whitelist_global_state, y_mint, y_vault) =
+        create_direct_sale(&mut banks_client, &payer, tree.root(), 0).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1_000_000_000),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &user.pubkey(),
+                &y_mint,
+                &spl_token::id(),
+            ),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let global_lamports_before = banks_client
+        .get_balance(whitelist_global_state)
+        .await
+        .unwrap();
+
+    // Amounts that would leave a fraction of a token unpaid for are rejected
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::buy_direct(
+            &user.pubkey(),
+            &whitelist_global_state,
+            &y_mint,
+            &y_vault,
+            USER_ALLOCATION - 1,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+            false,
+            false,
+        )],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::AmountNotPriceMultiple as u32)
+        )
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::buy_direct(
+            &user.pubkey(),
+            &whitelist_global_state,
+            &y_mint,
+            &y_vault,
+            USER_ALLOCATION,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+            false,
+            false,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let expected_y_amount = USER_ALLOCATION / PRICE_PER_TOKEN_Y;
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        expected_y_amount
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &y_vault).await,
+        POOL_Y_LIQUIDITY - expected_y_amount
+    );
+    assert_eq!(
+        banks_client
+            .get_balance(whitelist_global_state)
+            .await
+            .unwrap(),
+        global_lamports_before + USER_ALLOCATION
+    );
+
+    // The schedule is locked once the sale has raised SOL
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::set_sale_schedule(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            0,
+            0,
+            vec![],
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::SaleTermsLocked as u32)
+        )
+    );
+
+    // The pool swap is not available for a direct sale
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::withdraw_proceeds(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &y_vault,
+            &Pubkey::new_unique(),
+            &payer.pubkey(),
+            &native_mint::id(),
+            USER_ALLOCATION,
+            u64::MAX,
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::SaleModeMismatch as u32)
+        )
+    );
+
+    // The proceeds are withdrawn to the treasury, which defaults to the creator
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::withdraw_direct_proceeds(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &payer.pubkey(),
+            USER_ALLOCATION,
+            &[],
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        banks_client
+            .get_balance(whitelist_global_state)
+            .await
+            .unwrap(),
+        global_lamports_before
+    );
+    let account = banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = WhitelistPDAGlobalState::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.total_proceeds_withdrawn, USER_ALLOCATION);
+}
+
+#[tokio::test]
+async fn test_soft_cap_escrow() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+
+    let user = Keypair::new();
+    let tree = MerkleTree::new(&[AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }]);
+    let (whitelist_global_state, y_mint, y_vault) =
+        create_direct_sale(&mut banks_client, &payer, tree.root(), 4_000_000_000).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1_000_000_000),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &user.pubkey(),
+                &y_mint,
+                &spl_token::id(),
+            ),
+            instruction::set_sale_caps(
+                &payer.pubkey(),
+                &whitelist_global_state,
+                2 * USER_ALLOCATION,
+                3 * USER_ALLOCATION,
+                true,
+                &[],
+            ),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::buy_direct(
+            &user.pubkey(),
+            &whitelist_global_state,
+            &y_mint,
+            &y_vault,
+            USER_ALLOCATION,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+            false,
+            false,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    // The tokens bought stay escrowed in the vault
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        0
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &y_vault).await,
+        POOL_Y_LIQUIDITY
+    );
+
+    let account = banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = WhitelistPDAGlobalState::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.total_raised, USER_ALLOCATION);
+    assert_eq!(state.total_y_escrowed, USER_ALLOCATION / PRICE_PER_TOKEN_Y);
+
+    // Neither refunds nor claims are possible while the sale runs, pausing it or not
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[
+            instruction::pause(&payer.pubkey(), &whitelist_global_state, &[]),
+            instruction::refund(&user.pubkey(), &whitelist_global_state),
+        ],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(WhiteListError::SaleNotEnded as u32)
+        )
+    );
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::claim_tokens(
+            &user.pubkey(),
+            &whitelist_global_state,
+            &y_mint,
+            &y_vault,
+        )],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::SaleNotEnded as u32)
+        )
+    );
+
+    // The SOL raised stays refundable until the soft cap is reached
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::withdraw_direct_proceeds(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &payer.pubkey(),
+            USER_ALLOCATION,
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::SoftCapNotReached as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_vesting() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+
+    let user = Keypair::new();
+    let tree = MerkleTree::new(&[AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }]);
+    let (whitelist_global_state, y_mint, y_vault) =
+        create_direct_sale(&mut banks_client, &payer, tree.root(), 0).await;
+    let (vesting_vault, _) =
+        find_whitelist_vesting_vault_address(&whitelist_global_state, &user.pubkey());
+
+    // A quarter unlocks at once, the rest only after a cliff that is far away
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1_000_000_000),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &user.pubkey(),
+                &y_mint,
+                &spl_token::id(),
+            ),
+            instruction::set_vesting_schedule(
+                &payer.pubkey(),
+                &whitelist_global_state,
+                true,
+                i64::MAX / 2,
+                1_000,
+                2_500,
+                &[],
+            ),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Purchases of a vesting sale can't pay into the user's own token account
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::buy_direct(
+            &user.pubkey(),
+            &whitelist_global_state,
+            &y_mint,
+            &y_vault,
+            USER_ALLOCATION,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+            false,
+            false,
+        )],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::IncorrectStateAccount as u32)
+        )
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            instruction::create_vesting_vault(&user.pubkey(), &whitelist_global_state, &y_mint),
+            instruction::buy_direct(
+                &user.pubkey(),
+                &whitelist_global_state,
+                &y_mint,
+                &y_vault,
+                USER_ALLOCATION,
+                USER_ALLOCATION,
+                tree.proof(0).unwrap(),
+                false,
+                true,
+            ),
+        ],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let expected_y_amount = USER_ALLOCATION / PRICE_PER_TOKEN_Y;
+    assert_eq!(
+        get_token_balance(&mut banks_client, &vesting_vault).await,
+        expected_y_amount
+    );
+
+    // Pausing the sale doesn't lock up what has been bought
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            instruction::pause(&payer.pubkey(), &whitelist_global_state, &[]),
+            instruction::claim_vested(&user.pubkey(), &whitelist_global_state, &y_mint),
+        ],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &y_mint);
+    let tge_amount = expected_y_amount / 4;
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        tge_amount
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &vesting_vault).await,
+        expected_y_amount - tge_amount
+    );
+
+    let account = banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = WhitelistPDAGlobalState::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.total_y_vesting, expected_y_amount - tge_amount);
+
+    // Nothing else unlocks before the cliff, the transfer only keeps the transaction unique
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1),
+            instruction::claim_vested(&user.pubkey(), &whitelist_global_state, &y_mint),
+        ],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(WhiteListError::NothingToClaim as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_buy_with_sol() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+
+    // The user only has lamports, no token accounts
+    let user = Keypair::new();
+    process(
+        &mut banks_client,
+        &payer,
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &user.pubkey(),
+            1_000_000_000,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let tree = MerkleTree::new(&[AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }]);
+    let whitelist_global_state =
+        init_whitelist(&mut banks_client, &payer, &pool, tree.root()).await;
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::buy_with_sol(
+            &user.pubkey(),
+            &whitelist_global_state,
+            &pool.y_mint,
+            &pool.state.pubkey(),
+            &pool.quote_token_account,
+            &pool.y_token_account,
+            &pool.pool_mint,
+            &pool.pool_fee_account,
+            &pool.host_fee_account,
+            USER_ALLOCATION,
+            USER_ALLOCATION / PRICE_PER_TOKEN_Y,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+            false,
+            false,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &pool.y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        USER_ALLOCATION / PRICE_PER_TOKEN_Y
+    );
+
+    // The temporary wSOL account is closed again
+    let user_native_sol_token_account =
+        get_associated_token_address(&user.pubkey(), &native_mint::id());
+    assert!(banks_client
+        .get_account(user_native_sol_token_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_buy_with_sol_associated_accounts() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let user = Keypair::new();
+    let tree = MerkleTree::new(&[AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }]);
+    let whitelist_global_state =
+        init_whitelist(&mut banks_client, &payer, &pool, tree.root()).await;
+    let buy_with_sol_ix = instruction::buy_with_sol(
+        &user.pubkey(),
+        &whitelist_global_state,
+        &pool.y_mint,
+        &pool.state.pubkey(),
+        &pool.quote_token_account,
+        &pool.y_token_account,
+        &pool.pool_mint,
+        &pool.pool_fee_account,
+        &pool.host_fee_account,
+        USER_ALLOCATION,
+        USER_ALLOCATION / PRICE_PER_TOKEN_Y,
+        USER_ALLOCATION,
+        tree.proof(0).unwrap(),
+        false,
+        false,
+    );
+
+    // The SOL swapped alone doesn't cover the rent of the two associated accounts created
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(
+                &payer.pubkey(),
+                &user.pubkey(),
+                USER_ALLOCATION + rent.minimum_balance(TokenState::LEN),
+            ),
+            buy_with_sol_ix.clone(),
+        ],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(WhiteListError::InsufficientSol as u32)
+        )
+    );
+
+    // A wSOL account the user already has is left open
+    let user_native_sol_token_account =
+        get_associated_token_address(&user.pubkey(), &native_mint::id());
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1_000_000_000),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &user.pubkey(),
+                &native_mint::id(),
+                &spl_token::id(),
+            ),
+            buy_with_sol_ix,
+        ],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &pool.y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        USER_ALLOCATION / PRICE_PER_TOKEN_Y
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_native_sol_token_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_quote_mint() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let usdc_mint = create_mint(&mut banks_client, &payer, &payer.pubkey(), 6).await;
+    let pool = create_quoted_pool(&mut banks_client, &payer, &usdc_mint).await;
+
+    // The user pays from their USDC associated account
+    let user = Keypair::new();
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1_000_000_000),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &user.pubkey(),
+                &pool.y_mint,
+                &spl_token::id(),
+            ),
+            create_associated_token_account(
+                &payer.pubkey(),
+                &user.pubkey(),
+                &usdc_mint,
+                &spl_token::id(),
+            ),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let user_usdc_token_account = get_associated_token_address(&user.pubkey(), &usdc_mint);
+    mint_to(
+        &mut banks_client,
+        &payer,
+        &usdc_mint,
+        &user_usdc_token_account,
+        USER_ALLOCATION - PRICE_PER_TOKEN_Y,
+    )
+    .await;
+
+    let tree = MerkleTree::new(&[AllowlistEntry {
+        user: user.pubkey(),
+        allocation: USER_ALLOCATION,
+    }]);
+    let whitelist_global_state =
+        init_whitelist(&mut banks_client, &payer, &pool, tree.root()).await;
+
+    let account = banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = WhitelistPDAGlobalState::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.quote_mint, usdc_mint);
+
+    // Plain lamports can't buy into a sale quoted in USDC
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::buy_with_sol(
+            &user.pubkey(),
+            &whitelist_global_state,
+            &pool.y_mint,
+            &pool.state.pubkey(),
+            &pool.quote_token_account,
+            &pool.y_token_account,
+            &pool.pool_mint,
+            &pool.pool_fee_account,
+            &pool.host_fee_account,
+            USER_ALLOCATION,
+            USER_ALLOCATION / PRICE_PER_TOKEN_Y,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+            false,
+            false,
+        )],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::SaleModeMismatch as u32)
+        )
+    );
+
+    // Short of USDC
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[swap_sol_token(
+            &user,
+            &whitelist_global_state,
+            &pool,
+            USER_ALLOCATION,
+            USER_ALLOCATION,
+            tree.proof(0).unwrap(),
+        )],
+        &[&user],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::InsufficientFunds as u32)
+        )
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &usdc_mint,
+                &user_usdc_token_account,
+                &payer.pubkey(),
+                &[],
+                PRICE_PER_TOKEN_Y,
+            )
+            .unwrap(),
+            swap_sol_token(
+                &user,
+                &whitelist_global_state,
+                &pool,
+                USER_ALLOCATION,
+                USER_ALLOCATION,
+                tree.proof(0).unwrap(),
+            ),
+        ],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let user_y_token_account = get_associated_token_address(&user.pubkey(), &pool.y_mint);
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_y_token_account).await,
+        USER_ALLOCATION / PRICE_PER_TOKEN_Y
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_usdc_token_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_global_state() {
+    // A version 1 global state, at the address derived from the seeds it was created with
+    let creator = Keypair::new();
+    let user = Pubkey::new_unique();
+    let y_token_account = Pubkey::new_unique();
+    let (whitelist_global_state, whitelist_pda_bump) = Pubkey::find_program_address(
+        &[
+            WHITELIST_PDA_SEED,
+            &creator.pubkey().to_bytes(),
+            &y_token_account.to_bytes(),
+        ],
+        &whitelist_program::id(),
+    );
+    let mut whitelist_auth_addresses = [Pubkey::default(); 6];
+    whitelist_auth_addresses[0] = user;
+    let v1_state = WhitelistPDAGlobalStateV1 {
+        whitelist_creator: creator.pubkey(),
+        global_pda_bump: whitelist_pda_bump,
+        whitelist_auth_addresses,
+        is_initialized: true,
+        y_token_account,
+        price_per_token_y: PRICE_PER_TOKEN_Y,
+        ..WhitelistPDAGlobalStateV1::default()
+    };
+    let v1_data = v1_state.try_to_vec().unwrap();
+    assert_eq!(v1_data.len(), GLOBAL_STATE_V1_LEN);
+
+    let mut program_test = program_test();
+    program_test.add_account(
+        whitelist_global_state,
+        Account {
+            lamports: Rent::default().minimum_balance(GLOBAL_STATE_V1_LEN),
+            data: v1_data,
+            owner: whitelist_program::id(),
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, _) = program_test.start().await;
+
+    // Instructions reject the outdated layout
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::resume(
+            &creator.pubkey(),
+            &whitelist_global_state,
+            &[],
+        )],
+        &[&creator],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::InvalidStateVersion as u32)
+        )
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::migrate_global_state(
+            &payer.pubkey(),
+            &whitelist_global_state,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let account = banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = WhitelistPDAGlobalState::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.version, GLOBAL_STATE_VERSION);
+    assert_eq!(state.whitelist_creator, creator.pubkey());
+    assert_eq!(state.whitelist_authority, creator.pubkey());
+    assert_eq!(state.global_pda_bump, whitelist_pda_bump);
+    assert_eq!(state.price_per_token_y, PRICE_PER_TOKEN_Y);
+    assert!(state.has_legacy_seeds);
+    assert_eq!(
+        state.whitelist_merkle_root,
+        MerkleTree::new(&[AllowlistEntry {
+            user,
+            allocation: 0
+        }])
+        .root()
+    );
+    assert!(account.lamports >= Rent::default().minimum_balance(account.data.len()));
+
+    // Migrating twice is rejected
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &creator.pubkey(), 1),
+            instruction::migrate_global_state(&payer.pubkey(), &whitelist_global_state),
+        ],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(WhiteListError::InvalidStateVersion as u32)
+        )
+    );
+
+    // The migrated sale works again
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::pause(
+            &creator.pubkey(),
+            &whitelist_global_state,
+            &[],
+        )],
+        &[&creator],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_prefunded_addresses() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let pool = create_pool(&mut banks_client, &payer).await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    // Someone funds the predictable addresses ahead of time, a transfer to a
+    // system account has to leave it rent exempt so it can't be any smaller
+    let prefund = rent.minimum_balance(0);
+    let user = Keypair::new();
+    let (whitelist_global_state, _) =
+        find_whitelist_address(&payer.pubkey(), &pool.y_token_account, 0);
+    let user_native_sol_token_account =
+        get_associated_token_address(&user.pubkey(), &native_mint::id());
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 1_000_000_000),
+            system_instruction::transfer(&payer.pubkey(), &whitelist_global_state, prefund),
+            system_instruction::transfer(&payer.pubkey(), &user_native_sol_token_account, prefund),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    init_whitelist(&mut banks_client, &payer, &pool, [0u8; 32]).await;
+
+    // Only the rent still missing has been added
+    let account = banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(account.lamports > prefund);
+    assert_eq!(account.lamports, rent.minimum_balance(account.data.len()));
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[instruction::create_and_wrap_sol_token(
+            &user.pubkey(),
+            USER_ALLOCATION,
+            None,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    // The lamports already there counted towards the rent, the whole amount is wrapped
+    assert_eq!(
+        get_token_balance(&mut banks_client, &user_native_sol_token_account).await,
+        USER_ALLOCATION
+    );
+}
+
+#[tokio::test]
+async fn test_non_canonical_global_state_rejected() {
+    let creator = Keypair::new();
+    let y_token_account = Pubkey::new_unique();
+    let (canonical_global_state, whitelist_pda_bump) =
+        find_whitelist_address(&creator.pubkey(), &y_token_account, 0);
+    let state = WhitelistPDAGlobalState {
+        version: GLOBAL_STATE_VERSION,
+        whitelist_creator: creator.pubkey(),
+        global_pda_bump: whitelist_pda_bump,
+        seed_y_token_account: y_token_account,
+        is_initialized: true,
+        whitelist_authority: creator.pubkey(),
+        ..WhitelistPDAGlobalState::default()
+    };
+    let data = state.try_to_vec().unwrap();
+
+    // The same sale's state, once at another address of the program and once at the
+    // canonical address but recording another bump
+    let other_global_state = Pubkey::new_unique();
+    let mut wrong_bump_data = data.clone();
+    wrong_bump_data[1 + 32] = whitelist_pda_bump.wrapping_sub(1);
+
+    let mut program_test = program_test();
+    for (address, data) in vec![
+        (other_global_state, data),
+        (canonical_global_state, wrong_bump_data),
+    ] {
+        program_test.add_account(
+            address,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: whitelist_program::id(),
+                ..Account::default()
+            },
+        );
+    }
+    let (mut banks_client, payer, _) = program_test.start().await;
+
+    for whitelist_global_state in [other_global_state, canonical_global_state].iter() {
+        let result = process(
+            &mut banks_client,
+            &payer,
+            &[instruction::pause(
+                &creator.pubkey(),
+                whitelist_global_state,
+                &[],
+            )],
+            &[&creator],
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(WhiteListError::IncorrectPdaAccount as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_withdraw_unsold_tokens() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+
+    // Unsold tokens can't be withdrawn while the sale runs
+    let (running_sale, running_y_mint, running_y_vault) =
+        create_direct_sale(&mut banks_client, &payer, [0u8; 32], 4_000_000_000).await;
+    let running_y_token_account = get_associated_token_address(&payer.pubkey(), &running_y_mint);
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[
+            create_associated_token_account(
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &running_y_mint,
+                &spl_token::id(),
+            ),
+            instruction::withdraw_unsold_tokens(
+                &payer.pubkey(),
+                &running_sale,
+                &running_y_vault,
+                &running_y_token_account,
+                1,
+                &[],
+            ),
+        ],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(WhiteListError::SaleNotEnded as u32)
+        )
+    );
+
+    let (whitelist_global_state, y_mint, y_vault) =
+        create_direct_sale(&mut banks_client, &payer, [0u8; 32], 1).await;
+    let y_token_account = get_associated_token_address(&payer.pubkey(), &y_mint);
+    process(
+        &mut banks_client,
+        &payer,
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &y_mint,
+            &spl_token::id(),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // The end of the sale is final, even though it raised nothing
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::set_sale_schedule(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            0,
+            0,
+            vec![],
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::SaleEnded as u32)
+        )
+    );
+
+    // The sale can't be closed while its vault holds tokens only the PDA can move
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::close_whitelist(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &payer.pubkey(),
+            &y_vault,
+            None,
+            &payer.pubkey(),
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::VaultNotEmpty as u32)
+        )
+    );
+
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[instruction::withdraw_unsold_tokens(
+            &payer.pubkey(),
+            &whitelist_global_state,
+            &y_vault,
+            &y_token_account,
+            POOL_Y_LIQUIDITY + 1,
+            &[],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(WhiteListError::InvalidWithdrawAmount as u32)
+        )
+    );
+
+    // Lamports above the rent are proceeds that go to the treasury, not to the destination
+    let withdraw_unsold_tokens_ix = instruction::withdraw_unsold_tokens(
+        &payer.pubkey(),
+        &whitelist_global_state,
+        &y_vault,
+        &y_token_account,
+        POOL_Y_LIQUIDITY,
+        &[],
+    );
+    let close_whitelist_ix = instruction::close_whitelist(
+        &payer.pubkey(),
+        &whitelist_global_state,
+        &payer.pubkey(),
+        &y_vault,
+        None,
+        &payer.pubkey(),
+        &[],
+    );
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(
+                &payer.pubkey(),
+                &whitelist_global_state,
+                PRICE_PER_TOKEN_Y,
+            ),
+            withdraw_unsold_tokens_ix.clone(),
+            close_whitelist_ix.clone(),
+        ],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(WhiteListError::ProceedsOutstanding as u32)
+        )
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            system_instruction::transfer(
+                &payer.pubkey(),
+                &whitelist_global_state,
+                PRICE_PER_TOKEN_Y,
+            ),
+            instruction::withdraw_direct_proceeds(
+                &payer.pubkey(),
+                &whitelist_global_state,
+                &payer.pubkey(),
+                PRICE_PER_TOKEN_Y,
+                &[],
+            ),
+            withdraw_unsold_tokens_ix,
+            close_whitelist_ix,
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, &y_token_account).await,
+        POOL_Y_LIQUIDITY
+    );
+    assert!(banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_closed_account_not_reused() {
+    let (mut banks_client, payer, _) = program_test().start().await;
+    let (whitelist_global_state, y_mint, y_vault) =
+        create_direct_sale(&mut banks_client, &payer, [0u8; 32], 1).await;
+    let y_token_account = get_associated_token_address(&payer.pubkey(), &y_mint);
+    process(
+        &mut banks_client,
+        &payer,
+        &[
+            create_associated_token_account(
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &y_mint,
+                &spl_token::id(),
+            ),
+            instruction::withdraw_unsold_tokens(
+                &payer.pubkey(),
+                &whitelist_global_state,
+                &y_vault,
+                &y_token_account,
+                POOL_Y_LIQUIDITY,
+                &[],
+            ),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // A closed account belongs to the system program for the rest of the transaction
+    let close_whitelist_ix = instruction::close_whitelist(
+        &payer.pubkey(),
+        &whitelist_global_state,
+        &payer.pubkey(),
+        &y_vault,
+        None,
+        &payer.pubkey(),
+        &[],
+    );
+    let result = process(
+        &mut banks_client,
+        &payer,
+        &[
+            close_whitelist_ix.clone(),
+            instruction::pause(&payer.pubkey(), &whitelist_global_state, &[]),
+        ],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(WhiteListError::IncorrectStateAccount as u32)
+        )
+    );
+
+    process(&mut banks_client, &payer, &[close_whitelist_ix], &[])
+        .await
+        .unwrap();
+    assert!(banks_client
+        .get_account(whitelist_global_state)
+        .await
+        .unwrap()
+        .is_none());
+}
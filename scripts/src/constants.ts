@@ -53,4 +53,13 @@ export const TOKEN_ACCOUNT_PATHS: Record<string, string> = {
 export const TOTAL_ACCOUNTS_ALLOWED = 5;
 export const PRICE_PER_TOKEN_B = 2;
 
+// Version byte every whitelist instruction starts with
+export const INSTRUCTION_VERSION = 1;
+// Tells apart the creator's sales of the same Y token account
+export const SALE_ID = 0;
+
+export const WHITELIST_PDA_SEED = "whitelistpda";
+export const WHITELIST_USER_SEED = "whitelistuser";
+export const WHITELIST_REGISTRY_SEED = "whitelistregistry";
+
 export const SOLANA_CONNECTION = new Connection("http://localhost:8899/");
@@ -22,26 +22,61 @@ import { getPublicKey } from "../../utils/file";
         whiteListGlobalState.data
       );
 
+      const u64 = (value: Buffer) =>
+        Buffer.from(value).readBigUInt64LE().toString();
+      const i64 = (value: Buffer) =>
+        Buffer.from(value).readBigInt64LE().toString();
+      const pubkey = (value: Buffer) => new PublicKey(value).toString();
+
       console.log({
-        whitelistCreator: new PublicKey(
-          decodedState.whitelistCreator
-        ).toString(),
+        version: decodedState.version,
+        whitelistCreator: pubkey(decodedState.whitelistCreator),
         globalPDABump: decodedState.globalPDABump,
-        authorizedAddresses: (
-          Object.values(decodedState.authorizedAddresses) as Buffer[]
-        ).map((address: Buffer) => new PublicKey(address).toString()),
+        whitelistMerkleRoot: Buffer.from(
+          decodedState.whitelistMerkleRoot
+        ).toString("hex"),
         isInitialized: decodedState.isInitialized === 1,
-        tokenSwapPoolStateAccount: new PublicKey(
-          decodedState.tokenSwapPoolState
-        ).toString(),
-        yTokenMintAccount: new PublicKey(decodedState.yMintAccount).toString(),
-        yTokenAccount: new PublicKey(decodedState.yTokenAccount).toString(),
-        nativeSolTokenAccount: new PublicKey(
-          decodedState.nativeSolTokenAccount
-        ).toString(),
-        pricePerTokenY: parseInt(
-          Buffer.from(decodedState.pricePerTokenY).readUInt8().toString()
-        ),
+        tokenSwapPoolStateAccount: pubkey(decodedState.tokenSwapPoolState),
+        yTokenMintAccount: pubkey(decodedState.yMintAccount),
+        yTokenAccount: pubkey(decodedState.yTokenAccount),
+        quoteTokenAccount: pubkey(decodedState.quoteTokenAccount),
+        pricePerTokenY: u64(decodedState.pricePerTokenY),
+        whitelistAdmin: pubkey(decodedState.whitelistAdmin),
+        minContribution: u64(decodedState.minContribution),
+        maxContribution: u64(decodedState.maxContribution),
+        saleStartTs: i64(decodedState.saleStartTs),
+        saleEndTs: i64(decodedState.saleEndTs),
+        salePhases: decodedState.salePhases
+          .slice(0, decodedState.salePhaseCount)
+          .map((phase: any) => ({
+            startTs: i64(phase.startTs),
+            pricePerTokenY: u64(phase.pricePerTokenY),
+            maxContribution: u64(phase.maxContribution),
+            isPublic: phase.isPublic === 1,
+          })),
+        isFixedPrice: decodedState.isFixedPrice === 1,
+        isPaused: decodedState.isPaused === 1,
+        whitelistGuardian: pubkey(decodedState.whitelistGuardian),
+        whitelistAuthority: pubkey(decodedState.whitelistAuthority),
+        pendingAuthority: pubkey(decodedState.pendingAuthority),
+        treasury: pubkey(decodedState.treasury),
+        totalProceedsWithdrawn: u64(decodedState.totalProceedsWithdrawn),
+        seedYTokenAccount: pubkey(decodedState.seedYTokenAccount),
+        isDirectSale: decodedState.isDirectSale === 1,
+        softCap: u64(decodedState.softCap),
+        hardCap: u64(decodedState.hardCap),
+        totalRaised: u64(decodedState.totalRaised),
+        totalRefunded: u64(decodedState.totalRefunded),
+        isClaimLater: decodedState.isClaimLater === 1,
+        totalYEscrowed: u64(decodedState.totalYEscrowed),
+        isVesting: decodedState.isVesting === 1,
+        vestingCliffTs: i64(decodedState.vestingCliffTs),
+        vestingDuration: i64(decodedState.vestingDuration),
+        vestingTgeUnlockBps: decodedState.vestingTgeUnlockBps,
+        totalYVesting: u64(decodedState.totalYVesting),
+        saleId: u64(decodedState.saleId),
+        quoteMint: pubkey(decodedState.quoteMint),
+        hasLegacySeeds: decodedState.hasLegacySeeds === 1,
       });
 
       return;
@@ -27,11 +27,24 @@ import { getPublicKey } from "../../utils/file";
           decodedState.whiteListedByAccount
         ).toString(),
         whitelistedAt: Buffer.from(decodedState.whitelistedAt)
-          .readBigUInt64LE()
+          .readBigInt64LE()
           .toString(),
         userTransferAuthorityAccount: new PublicKey(
           decodedState.userTransferAuthorityAccount
         ).toString(),
+        totalContributed: Buffer.from(decodedState.totalContributed)
+          .readBigUInt64LE()
+          .toString(),
+        totalReceived: Buffer.from(decodedState.totalReceived)
+          .readBigUInt64LE()
+          .toString(),
+        userStateBump: decodedState.userStateBump,
+        totalRefunded: Buffer.from(decodedState.totalRefunded)
+          .readBigUInt64LE()
+          .toString(),
+        totalClaimed: Buffer.from(decodedState.totalClaimed)
+          .readBigUInt64LE()
+          .toString(),
       });
 
       return;
@@ -28,7 +28,6 @@ import {
     await storeKeypair("masterAccount", "persons", true);
     await storeKeypair("whitelistCreator", "persons", true);
     await storeKeypair("tokenSwap", "state", true);
-    await storeKeypair("whiteListUser", "state", true);
   } catch (err) {
     console.error(err);
   }
@@ -1,15 +1,18 @@
 import {
-  Keypair,
   LAMPORTS_PER_SOL,
   PublicKey,
   Transaction,
   TransactionInstruction,
 } from "@solana/web3.js";
 import {
+  INSTRUCTION_VERSION,
   PRICE_PER_TOKEN_B,
+  SALE_ID,
   SOLANA_CONNECTION,
   SYSTEM_PROGRAM_ID,
+  WHITELIST_PDA_SEED,
   WHITELIST_PROGRAM_ID,
+  WHITELIST_REGISTRY_SEED,
 } from "../../constants";
 import {
   checkKeysDir,
@@ -19,6 +22,8 @@ import {
   sleep,
   storePublicKey,
 } from "../../utils/file";
+import { getInitIxLayout } from "../../utils/layout";
+import { getAllowlist, MerkleTree } from "../../utils/merkle";
 import BN from "bn.js";
 
 (async function () {
@@ -32,20 +37,22 @@ import BN from "bn.js";
       wlstMint,
     } = await checkForPreRequisites();
 
-    const [whitelistCreatorBuf, w1, w2, w3, w4, w5] = [
-      whitelistCreator.publicKey.toBuffer(),
-      user.publicKey.toBuffer(),
-      ...[1, 2, 3, 4].map(() => {
-        const pubkey = Keypair.generate().publicKey;
-        return pubkey.toBuffer();
-      }),
-    ];
+    const allowlist = getAllowlist(whitelistCreator.publicKey, user.publicKey);
 
-    const [whitelistProgramPDA, bump] = await PublicKey.findProgramAddress(
+    const [whitelistProgramPDA] = await PublicKey.findProgramAddress(
       [
-        Buffer.from("whitelistpda"),
+        Buffer.from(WHITELIST_PDA_SEED),
         whitelistCreator.publicKey.toBytes(),
         wlstTokenAccount.toBytes(),
+        Buffer.from(new BN(SALE_ID).toArray("le", 8)),
+      ],
+      WHITELIST_PROGRAM_ID
+    );
+
+    const [whitelistRegistryPDA] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from(WHITELIST_REGISTRY_SEED),
+        whitelistCreator.publicKey.toBytes(),
       ],
       WHITELIST_PROGRAM_ID
     );
@@ -60,12 +67,35 @@ import BN from "bn.js";
 
     console.log("GENERATED PDA", whitelistProgramPDA.toString());
 
+    const INIT_IX_LAYOUT = getInitIxLayout();
+    const initIxData = Buffer.alloc(INIT_IX_LAYOUT.span);
+    INIT_IX_LAYOUT.encode(
+      {
+        version: INSTRUCTION_VERSION,
+        tag: 0,
+        pricePerTokenY: Buffer.from(
+          new BN((PRICE_PER_TOKEN_B * LAMPORTS_PER_SOL) / 10 ** 2).toArray(
+            "le",
+            8
+          )
+        ),
+        merkleRoot: new MerkleTree(allowlist).root(),
+        minContribution: Buffer.from(new BN(0).toArray("le", 8)),
+        maxContribution: Buffer.from(new BN(0).toArray("le", 8)),
+        saleStartTs: Buffer.from(new BN(0).toArray("le", 8)),
+        saleEndTs: Buffer.from(new BN(0).toArray("le", 8)),
+        isFixedPrice: 1,
+        saleId: Buffer.from(new BN(SALE_ID).toArray("le", 8)),
+      },
+      initIxData
+    );
+
     const initWhiteListIx = new TransactionInstruction({
       programId: WHITELIST_PROGRAM_ID,
       keys: [
         {
           isSigner: true,
-          isWritable: false,
+          isWritable: true,
           pubkey: whitelistCreator.publicKey,
         },
         {
@@ -98,21 +128,13 @@ import BN from "bn.js";
           isWritable: false,
           pubkey: SYSTEM_PROGRAM_ID,
         },
+        {
+          isSigner: false,
+          isWritable: true,
+          pubkey: whitelistRegistryPDA,
+        },
       ],
-      data: Buffer.from([
-        0,
-        bump,
-        ...new BN((PRICE_PER_TOKEN_B * LAMPORTS_PER_SOL) / 10 ** 2).toArray(
-          "le",
-          8
-        ),
-        ...whitelistCreatorBuf,
-        ...w1,
-        ...w2,
-        ...w3,
-        ...w4,
-        ...w5,
-      ]),
+      data: initIxData,
     });
 
     await SOLANA_CONNECTION.sendTransaction(
@@ -2,19 +2,22 @@ import {
   Keypair,
   LAMPORTS_PER_SOL,
   PublicKey,
-  SystemProgram,
   Transaction,
   TransactionInstruction,
 } from "@solana/web3.js";
 import BN from "bn.js";
-import { getWhiteListUserStateLayout } from "../../utils/layout";
+import { getSwapSolIxLayout } from "../../utils/layout";
+import { getAllowlist, MerkleTree } from "../../utils/merkle";
 import {
+  INSTRUCTION_VERSION,
   PRICE_PER_TOKEN_B,
   SOLANA_CONNECTION,
+  SYSTEM_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
   TOKEN_SWAP_FEE_OWNER,
   TOKEN_SWAP_PROGRAM_ID,
   WHITELIST_PROGRAM_ID,
+  WHITELIST_USER_SEED,
 } from "../../constants";
 import {
   checkKeysDir,
@@ -23,7 +26,7 @@ import {
   getPublicKey,
   getTokenAccount,
   sleep,
-  storeKeypair,
+  storePublicKey,
   storeTokenAccount,
 } from "../../utils/file";
 
@@ -31,12 +34,12 @@ import {
   try {
     const {
       user,
+      whitelistCreator,
       wlstMintAccount,
       wsolMintAccount,
       tokenSwapStateAccount,
       userNativeSolTokenAccount,
       swapAuthorityPDA,
-      whitelistUserStateAccount,
       whitelistGlobalStateAccount,
       poolWlstTokenAccount,
       poolWsolTokenAccount,
@@ -44,10 +47,20 @@ import {
       poolFeeTokenAccount,
     } = await checkForPreRequisites();
 
+    const [whitelistUserStatePDA] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from(WHITELIST_USER_SEED),
+        whitelistGlobalStateAccount.toBytes(),
+        user.publicKey.toBytes(),
+      ],
+      WHITELIST_PROGRAM_ID
+    );
+
     console.log(
       "WHITELIST USER STATE ACCOUNT",
-      whitelistUserStateAccount.publicKey.toString()
+      whitelistUserStatePDA.toString()
     );
+    await storePublicKey("whiteListUser", "state", whitelistUserStatePDA, true);
 
     // Fetching or Creating Token
     console.log("FETCHING/CREATING TOKEN Y ACCOUNT FOR USER");
@@ -67,33 +80,45 @@ import {
     );
 
     // Instruction phase
-    const WHITELIST_USER_STATE_LAYOUT = getWhiteListUserStateLayout();
-
-    const initWhitelistUserStateIx = SystemProgram.createAccount({
-      fromPubkey: user.publicKey,
-      lamports: await SOLANA_CONNECTION.getMinimumBalanceForRentExemption(
-        WHITELIST_USER_STATE_LAYOUT.span
-      ),
-      newAccountPubkey: whitelistUserStateAccount.publicKey,
-      programId: WHITELIST_PROGRAM_ID,
-      space: WHITELIST_USER_STATE_LAYOUT.span,
-    });
+    const allowlist = getAllowlist(whitelistCreator.publicKey, user.publicKey);
+    const proof = new MerkleTree(allowlist).proof(1);
+
+    const SWAP_SOL_IX_LAYOUT = getSwapSolIxLayout(proof.length);
+    const swapSolIxData = Buffer.alloc(SWAP_SOL_IX_LAYOUT.span);
+    SWAP_SOL_IX_LAYOUT.encode(
+      {
+        version: INSTRUCTION_VERSION,
+        tag: 4,
+        inputSolAmount: Buffer.from(
+          new BN(PRICE_PER_TOKEN_B * LAMPORTS_PER_SOL).toArray("le", 8)
+        ),
+        expectedSplTokenAmount: Buffer.from(
+          new BN(1 * 10 ** 2).toArray("le", 8)
+        ),
+        allocation: Buffer.from(
+          new BN(allowlist[1].allocation).toArray("le", 8)
+        ),
+        proofLength: proof.length,
+        proof,
+      },
+      swapSolIxData
+    );
 
     const swapSOLForSPLIx = new TransactionInstruction({
       keys: [
         {
           isSigner: true,
-          isWritable: false,
+          isWritable: true,
           pubkey: user.publicKey,
         },
         {
           isSigner: false,
           isWritable: true,
-          pubkey: whitelistUserStateAccount.publicKey,
+          pubkey: whitelistUserStatePDA,
         },
         {
           isSigner: false,
-          isWritable: false,
+          isWritable: true,
           pubkey: whitelistGlobalStateAccount,
         },
         {
@@ -156,24 +181,23 @@ import {
           isWritable: false,
           pubkey: TOKEN_SWAP_PROGRAM_ID,
         },
+        {
+          isSigner: false,
+          isWritable: false,
+          pubkey: SYSTEM_PROGRAM_ID,
+        },
       ],
       programId: WHITELIST_PROGRAM_ID,
-      data: Buffer.from([
-        4,
-        ...new BN(PRICE_PER_TOKEN_B * LAMPORTS_PER_SOL).toArray("le", 8),
-        ...new BN(1 * 10 ** 2).toArray("le", 8),
-      ]),
+      data: swapSolIxData,
     });
 
-    const swapTransaction = new Transaction().add(
-      initWhitelistUserStateIx,
-      swapSOLForSPLIx
-    );
+    // The program creates the user state PDA on the user's first swap
+    const swapTransaction = new Transaction().add(swapSOLForSPLIx);
 
     console.log(`SWAPPING ${PRICE_PER_TOKEN_B} SOL FOR 1 WLST`);
     await SOLANA_CONNECTION.sendTransaction(
       swapTransaction,
-      [user, whitelistUserStateAccount, tempAuthTokenAccount],
+      [user, tempAuthTokenAccount],
       {
         preflightCommitment: "confirmed",
         skipPreflight: false,
@@ -207,24 +231,9 @@ async function checkForPreRequisites() {
 
   const user = await getKeyPair("user", "persons");
   const whitelistCreator = await getKeyPair("whitelistCreator", "persons");
-  let whitelistUserStateAccount = await getKeyPair("whiteListUser", "state");
-
-  if (!user || !whitelistCreator || !whitelistUserStateAccount) {
-    throw new Error("Required person/state accounts doesn't exist");
-  }
-
-  if (
-    await SOLANA_CONNECTION.getAccountInfo(whitelistUserStateAccount.publicKey)
-  ) {
-    whitelistUserStateAccount = await storeKeypair(
-      "whiteListUser",
-      "state",
-      true
-    );
-  }
 
-  if (!whitelistUserStateAccount) {
-    throw new Error("Whitelist User state account not found");
+  if (!user || !whitelistCreator) {
+    throw new Error("Required person accounts doesn't exist");
   }
 
   if (!(await SOLANA_CONNECTION.getBalance(user.publicKey))) {
@@ -325,6 +334,5 @@ async function checkForPreRequisites() {
     poolMintToken,
     tokenSwapStateAccount,
     whitelistGlobalStateAccount,
-    whitelistUserStateAccount,
   };
 }
@@ -8,6 +8,7 @@ import {
 
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  INSTRUCTION_VERSION,
   NATIVE_MINT,
   SOLANA_CONNECTION,
   TOKEN_PROGRAM_ID,
@@ -61,7 +62,7 @@ import { checkKeysDir, getKeyPair, sleep } from "../../utils/file";
 })();
 
 async function unwrapSOL(owner: Keypair, tokenAccount: PublicKey) {
-  const instructionData = Buffer.from(Uint8Array.of(3));
+  const instructionData = Buffer.from(Uint8Array.of(INSTRUCTION_VERSION, 3));
 
   const unwrapSOLIx = new TransactionInstruction({
     keys: [
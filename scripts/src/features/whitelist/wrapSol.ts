@@ -10,6 +10,7 @@ import {
 import BN from "bn.js";
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  INSTRUCTION_VERSION,
   NATIVE_MINT,
   SOLANA_CONNECTION,
   SYSTEM_PROGRAM_ID,
@@ -22,6 +23,7 @@ import {
   sleep,
   storeTokenAccount,
 } from "../../utils/file";
+import { getWrapSolIxLayout } from "../../utils/layout";
 
 (async function () {
   try {
@@ -99,8 +101,15 @@ async function createAndWrapSOL(
   owner: Keypair,
   tokenAccount: PublicKey
 ) {
-  const instructionData = Buffer.from(
-    Uint8Array.of(1, ...new BN(amount).toArray("le", 8))
+  const WRAP_SOL_IX_LAYOUT = getWrapSolIxLayout();
+  const instructionData = Buffer.alloc(WRAP_SOL_IX_LAYOUT.span);
+  WRAP_SOL_IX_LAYOUT.encode(
+    {
+      version: INSTRUCTION_VERSION,
+      tag: 1,
+      amountToBeWrapped: Buffer.from(new BN(amount).toArray("le", 8)),
+    },
+    instructionData
   );
 
   const createAndWrapSOLIx = new TransactionInstruction({
@@ -171,8 +180,15 @@ async function wrapSOL(
   owner: Keypair,
   tokenAccount: PublicKey
 ) {
-  const instructionData = Buffer.from(
-    Uint8Array.of(2, ...new BN(amount).toArray("le", 8))
+  const WRAP_SOL_IX_LAYOUT = getWrapSolIxLayout();
+  const instructionData = Buffer.alloc(WRAP_SOL_IX_LAYOUT.span);
+  WRAP_SOL_IX_LAYOUT.encode(
+    {
+      version: INSTRUCTION_VERSION,
+      tag: 2,
+      amountToBeWrapped: Buffer.from(new BN(amount).toArray("le", 8)),
+    },
+    instructionData
   );
 
   const wrapSOLIx = new TransactionInstruction({
@@ -1,12 +1,13 @@
 export type WhiteListKeyType = "persons" | "tokens" | "mints" | "state";
 export type WhiteListGlobalState = {
+  version: number;
   whitelistCreator: string;
   globalPDABump: number;
-  authorizedAddresses: string[];
+  whitelistMerkleRoot: string;
   isInitialized: boolean;
   tokenSwapPoolStateAccount: string;
   yTokenMintAccount: string;
   yTokenAccount: string;
-  nativeSolTokenAccount: string;
-  pricePerTokenY: number;
+  quoteTokenAccount: string;
+  pricePerTokenY: string;
 };
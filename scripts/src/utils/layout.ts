@@ -16,41 +16,105 @@ export const uint64 = (property: string = "uint64"): BufferLayout.Blob => {
   return BufferLayout.blob(8, property);
 };
 
+/**
+ * Layout for a 64bit signed value, such as a unix timestamp
+ */
+export const int64 = (property: string = "int64"): BufferLayout.Blob => {
+  return BufferLayout.blob(8, property);
+};
+
+// Instructions are a version byte followed by their Borsh encoding, whose first byte is the tag
 export const getInitIxLayout = () => {
   return BufferLayout.struct([
+    BufferLayout.u8("version"),
     BufferLayout.u8("tag"),
-    BufferLayout.u8("bump"),
     uint64("pricePerTokenY"),
-    publicKey("whitelistCreator"),
-    publicKey("w1"),
-    publicKey("w2"),
-    publicKey("w3"),
-    publicKey("w4"),
-    publicKey("w5"),
+    BufferLayout.blob(32, "merkleRoot"),
+    uint64("minContribution"),
+    uint64("maxContribution"),
+    int64("saleStartTs"),
+    int64("saleEndTs"),
+    BufferLayout.u8("isFixedPrice"),
+    uint64("saleId"),
   ]);
 };
 
+export const getWrapSolIxLayout = () => {
+  return BufferLayout.struct([
+    BufferLayout.u8("version"),
+    BufferLayout.u8("tag"),
+    uint64("amountToBeWrapped"),
+  ]);
+};
+
+export const getSwapSolIxLayout = (proofLength: number) => {
+  return BufferLayout.struct([
+    BufferLayout.u8("version"),
+    BufferLayout.u8("tag"),
+    uint64("inputSolAmount"),
+    uint64("expectedSplTokenAmount"),
+    uint64("allocation"),
+    BufferLayout.u32("proofLength"),
+    BufferLayout.seq(BufferLayout.blob(32), proofLength, "proof"),
+  ]);
+};
+
+export const getSalePhaseLayout = (property: string = "salePhase") => {
+  return BufferLayout.struct(
+    [
+      int64("startTs"),
+      uint64("pricePerTokenY"),
+      uint64("maxContribution"),
+      BufferLayout.u8("isPublic"),
+    ],
+    property
+  );
+};
+
+export const MAX_SALE_PHASES = 4;
+
 export const getWhitelistStateLayout = () => {
   return BufferLayout.struct([
+    BufferLayout.u8("version"),
     publicKey("whitelistCreator"),
     BufferLayout.u8("globalPDABump"),
-    BufferLayout.struct(
-      [
-        publicKey("whitelistCreator"),
-        publicKey("w1"),
-        publicKey("w2"),
-        publicKey("w3"),
-        publicKey("w4"),
-        publicKey("w5"),
-      ],
-      "authorizedAddresses"
-    ),
+    BufferLayout.blob(32, "whitelistMerkleRoot"),
     BufferLayout.u8("isInitialized"),
     publicKey("tokenSwapPoolState"),
     publicKey("yMintAccount"),
     publicKey("yTokenAccount"),
-    publicKey("nativeSolTokenAccount"),
+    publicKey("quoteTokenAccount"),
     uint64("pricePerTokenY"),
+    publicKey("whitelistAdmin"),
+    uint64("minContribution"),
+    uint64("maxContribution"),
+    int64("saleStartTs"),
+    int64("saleEndTs"),
+    BufferLayout.u8("salePhaseCount"),
+    BufferLayout.seq(getSalePhaseLayout(), MAX_SALE_PHASES, "salePhases"),
+    BufferLayout.u8("isFixedPrice"),
+    BufferLayout.u8("isPaused"),
+    publicKey("whitelistGuardian"),
+    publicKey("whitelistAuthority"),
+    publicKey("pendingAuthority"),
+    publicKey("treasury"),
+    uint64("totalProceedsWithdrawn"),
+    publicKey("seedYTokenAccount"),
+    BufferLayout.u8("isDirectSale"),
+    uint64("softCap"),
+    uint64("hardCap"),
+    uint64("totalRaised"),
+    uint64("totalRefunded"),
+    BufferLayout.u8("isClaimLater"),
+    uint64("totalYEscrowed"),
+    BufferLayout.u8("isVesting"),
+    int64("vestingCliffTs"),
+    int64("vestingDuration"),
+    BufferLayout.u16("vestingTgeUnlockBps"),
+    uint64("totalYVesting"),
+    uint64("saleId"),
+    publicKey("quoteMint"),
+    BufferLayout.u8("hasLegacySeeds"),
   ]);
 };
 
@@ -58,7 +122,12 @@ export const getWhiteListUserStateLayout = () => {
   return BufferLayout.struct([
     BufferLayout.u8("isInitialized"),
     publicKey("whiteListedByAccount"),
-    uint64("whitelistedAt"),
+    int64("whitelistedAt"),
     publicKey("userTransferAuthorityAccount"),
+    uint64("totalContributed"),
+    uint64("totalReceived"),
+    BufferLayout.u8("userStateBump"),
+    uint64("totalRefunded"),
+    uint64("totalClaimed"),
   ]);
 };
@@ -0,0 +1,95 @@
+import { createHash } from "crypto";
+import { PublicKey } from "@solana/web3.js";
+import BN from "bn.js";
+
+// Same hashing as the program's merkle module, leaves and nodes being domain separated
+const LEAF_PREFIX = Buffer.from([0]);
+const NODE_PREFIX = Buffer.from([1]);
+
+export type AllowlistEntry = {
+  user: PublicKey;
+  // 0 means the user has no per-user cap
+  allocation: number;
+};
+
+const sha256 = (...parts: Buffer[]) =>
+  createHash("sha256").update(Buffer.concat(parts)).digest();
+
+export const leafHash = (user: PublicKey, allocation: number) =>
+  sha256(
+    LEAF_PREFIX,
+    user.toBuffer(),
+    Buffer.from(new BN(allocation).toArray("le", 8))
+  );
+
+// The pair is sorted first, so proofs don't need direction bits
+export const nodeHash = (a: Buffer, b: Buffer) =>
+  Buffer.compare(a, b) <= 0
+    ? sha256(NODE_PREFIX, a, b)
+    : sha256(NODE_PREFIX, b, a);
+
+/**
+ * Builds the allowlist Merkle tree the program verifies proofs against
+ */
+export class MerkleTree {
+  // layers[0] are the leaves, the last layer holds only the root
+  layers: Buffer[][];
+
+  constructor(entries: AllowlistEntry[]) {
+    this.layers = [
+      entries.map(({ user, allocation }) => leafHash(user, allocation)),
+    ];
+
+    while (this.layers[this.layers.length - 1].length > 1) {
+      const layer = this.layers[this.layers.length - 1];
+      const nextLayer: Buffer[] = [];
+
+      for (let index = 0; index < layer.length; index += 2) {
+        // Odd node out gets promoted to the next layer as is
+        nextLayer.push(
+          index + 1 < layer.length
+            ? nodeHash(layer[index], layer[index + 1])
+            : layer[index]
+        );
+      }
+
+      this.layers.push(nextLayer);
+    }
+  }
+
+  root(): Buffer {
+    return this.layers[this.layers.length - 1][0] || Buffer.alloc(32);
+  }
+
+  /**
+   * Proof for the leaf at `index`, from the bottom of the tree upwards
+   */
+  proof(index: number): Buffer[] {
+    if (index >= this.layers[0].length) {
+      throw new Error("Allowlist entry not found");
+    }
+
+    const proof: Buffer[] = [];
+
+    for (const layer of this.layers.slice(0, -1)) {
+      const sibling = layer[index ^ 1];
+      if (sibling) {
+        proof.push(sibling);
+      }
+      index = Math.floor(index / 2);
+    }
+
+    return proof;
+  }
+}
+
+/**
+ * Allowlist the scripts run their sale with, the creator and the user having no per-user cap
+ */
+export const getAllowlist = (
+  whitelistCreator: PublicKey,
+  user: PublicKey
+): AllowlistEntry[] => [
+  { user: whitelistCreator, allocation: 0 },
+  { user, allocation: 0 },
+];